@@ -0,0 +1,97 @@
+//! Authenticated requests to api endpoints the other commands don't cover
+use crate::Config;
+use futures::{StreamExt, TryStreamExt};
+use reqwest::Method;
+use serde_json::Value;
+use std::{error::Error, pin::Pin};
+use structopt::StructOpt;
+
+/// 🔌 Make an authenticated request to any GitHub api endpoint, printing its response
+#[derive(StructOpt, Debug)]
+pub struct Api {
+    /// Http method, i.e. GET, POST, PUT, PATCH or DELETE
+    #[structopt(parse(try_from_str = parse_method))]
+    method: Method,
+    /// Api path, i.e. /repos/owner/repo/actions/runs
+    path: String,
+    /// Field in the form key=value, sent as a query parameter of GET requests and in the json
+    /// body of other requests. Numbers, true, false and null are sent as such. May be provided multiple times
+    #[structopt(short = "f", long = "field", name = "field", parse(try_from_str = parse_field))]
+    fields: Vec<(String, Value)>,
+    /// Follow `next` links to print every page of results of a GET request
+    #[structopt(long)]
+    paginate: bool,
+}
+
+fn parse_method(method: &str) -> Result<Method, String> {
+    Method::from_bytes(method.to_uppercase().as_bytes())
+        .map_err(|_| format!("{} is not a valid http method", method))
+}
+
+/// Parses a key=value field, keeping values which are json numbers, booleans or null as such
+fn parse_field(field: &str) -> Result<(String, Value), String> {
+    let (name, value) = field
+        .split_once('=')
+        .ok_or_else(|| format!("{} is not a valid field. try key=value instead", field))?;
+    let value = match serde_json::from_str(value) {
+        Ok(value @ Value::Number(_)) | Ok(value @ Value::Bool(_)) | Ok(value @ Value::Null) => {
+            value
+        }
+        _ => Value::String(value.into()),
+    };
+    Ok((name.into(), value))
+}
+
+pub async fn api(
+    args: Api,
+    config: Config,
+) -> Result<(), Box<dyn Error>> {
+    let Api {
+        method,
+        path,
+        fields,
+        paginate,
+    } = args;
+    let requests = config.requests()?;
+    if paginate {
+        if method != Method::GET {
+            return Err(crate::StringErr("Only GET requests can be paginated".into()).into());
+        }
+        let mut pages = requests.api_pages(&path, &fields).boxed();
+        while let Some(page) = Pin::new(&mut pages).try_next().await? {
+            println!("{}", serde_json::to_string_pretty(&page)?);
+        }
+        return Ok(());
+    }
+    let body = requests.api(method, &path, &fields).await?;
+    match serde_json::from_str::<Value>(&body) {
+        Ok(json) => println!("{}", serde_json::to_string_pretty(&json)?),
+        Err(_) if body.is_empty() => (),
+        Err(_) => println!("{}", body),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_field_keeps_json_scalars() {
+        assert_eq!(parse_field("per_page=5"), Ok(("per_page".into(), json!(5))));
+        assert_eq!(
+            parse_field("enabled=true"),
+            Ok(("enabled".into(), json!(true)))
+        );
+        assert_eq!(
+            parse_field("ref=v1.0=final"),
+            Ok(("ref".into(), json!("v1.0=final")))
+        );
+        assert_eq!(
+            parse_field("name=\"5\""),
+            Ok(("name".into(), json!("\"5\"")))
+        );
+        assert!(parse_field("ref").is_err());
+    }
+}
@@ -1,55 +1,708 @@
-use crate::{github::Requests, StringErr};
-use futures::stream::StreamExt;
-use reqwest::Client;
-use std::{env, error::Error, pin::Pin};
+use crate::{
+    confirm,
+    format::{Format, Table},
+    github::{Artifact, Requests, RunFilter},
+    print_fields, print_json, print_json_lines,
+    workflows::resolve_workflow,
+    Config, Output, StringErr,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use colored::Colorize;
+use futures::{future, stream, StreamExt, TryFutureExt, TryStreamExt};
+use humantime::parse_duration;
+use std::{
+    cell::Cell,
+    cmp::Reverse,
+    error::Error,
+    io::{self, stderr, Write},
+    path::{Path, PathBuf},
+    pin::Pin,
+    rc::Rc,
+    time::Duration,
+};
 use structopt::StructOpt;
+use tokio::{fs::File, io::AsyncWriteExt};
 
 /// 📦 Get workflow artifacts
 #[derive(StructOpt, Debug)]
 pub enum Artifacts {
-    /// List repository secrets
+    /// List the artifacts of a workflow run, or of a workflow's most recent runs
     List {
-        /// GitHub repository in the form owner/repo
-        #[structopt(short, long, env = "ACTIONS_REPOSITORY")]
-        repository: String,
+        /// Id of run
+        #[structopt(long, required_unless = "workflow")]
+        run_id: Option<usize>,
+        /// Workflow file name or name whose recent runs' artifacts are listed, in place of --run-id
+        #[structopt(short, long, conflicts_with = "run-id")]
+        workflow: Option<String>,
+        /// Number of the workflow's most recent runs to list artifacts of. Only applies with --workflow
+        #[structopt(long, default_value = "1")]
+        last: usize,
+    },
+    /// Download a workflow run artifact's zip archive
+    Download {
+        /// Id of run
+        #[structopt(long, required_unless = "workflow")]
+        run_id: Option<usize>,
+        /// Workflow file name or name whose most recent run with the artifact --name is
+        /// downloaded from, in place of --run-id
+        #[structopt(short, long, conflicts_with = "run-id", requires = "name")]
+        workflow: Option<String>,
+        /// Only download from the workflow's most recent successful run with the artifact
+        #[structopt(long, requires = "workflow")]
+        latest_success: bool,
+        /// Id of artifact to download
+        #[structopt(short, long, required_unless = "name")]
+        artifact_id: Option<usize>,
+        /// Name of artifact to download, in place of --artifact-id
+        #[structopt(short, long, conflicts_with = "artifact-id")]
+        name: Option<String>,
+        /// File to write the archive to. Defaults to <artifact-name>.zip
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Download every unexpired artifact of a workflow run into a directory, each as
+    /// <artifact-name>.zip
+    DownloadAll {
         /// Id of run
         #[structopt(long)]
         run_id: usize,
+        /// Directory to save archives in, created when missing
+        #[structopt(long, default_value = ".", parse(from_os_str))]
+        dir: PathBuf,
+    },
+    /// Sum the size of a workflow's recent runs' artifacts, to see what uses artifact storage.
+    /// Expired artifacts no longer use storage, so they are skipped
+    Size {
+        /// Workflow file name or name
+        #[structopt(short, long, env = "ACTIONS_WORKFLOW")]
+        workflow: String,
+        /// Number of the workflow's most recent runs to sum artifacts of
+        #[structopt(long, default_value = "10")]
+        last: usize,
+        /// Also list this many of the largest artifacts
+        #[structopt(long)]
+        top: Option<usize>,
     },
     /// Delete a workflow run artifact
     Delete {
-        /// GitHub repository in the form owner/repo
-        #[structopt(short, long, env = "ACTIONS_REPOSITORY")]
-        repository: String,
         /// Id of artifact to delete
         #[structopt(short, long)]
         artifact_id: usize,
+        /// Delete without asking for confirmation
+        #[structopt(short, long)]
+        yes: bool,
+    },
+    /// Delete many of a workflow run's artifacts at once
+    Prune {
+        /// Id of run
+        #[structopt(long)]
+        run_id: usize,
+        /// Only delete artifacts that have expired
+        #[structopt(long)]
+        expired_only: bool,
+        /// Only delete artifacts created longer than this ago, i.e. 7days
+        #[structopt(long, parse(try_from_str = parse_duration))]
+        older_than: Option<Duration>,
     },
 }
 
-pub async fn artifacts(args: Artifacts) -> Result<(), Box<dyn Error>> {
+pub async fn artifacts(
+    args: Artifacts,
+    config: Config,
+) -> Result<(), Box<dyn Error>> {
     match args {
-        Artifacts::List { repository, run_id } => {
-            let client = Client::new();
-            let token = env::var("GITHUB_TOKEN")
-                .map_err(|_| StringErr("Please provide a GITHUB_TOKEN env variable".into()))?;
-            let requests = Requests { client, token };
-            let mut artifacts = requests.clone().artifacts(repository, run_id).boxed();
-            while let Some(artifact) = Pin::new(&mut artifacts).next().await {
-                println!("{}", artifact.name);
+        Artifacts::List {
+            run_id,
+            workflow,
+            last,
+        } => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let artifacts = match (run_id, workflow) {
+                (Some(run_id), _) => requests
+                    .clone()
+                    .artifacts(repository, run_id)
+                    .map_ok(move |artifact| (run_id, artifact))
+                    .boxed(),
+                (None, Some(workflow)) => {
+                    let workflow = resolve_workflow(&requests, &repository, &workflow).await?;
+                    let runs = requests.clone().runs(
+                        repository.clone(),
+                        workflow.id.to_string(),
+                        RunFilter {
+                            since: Utc.timestamp(0, 0),
+                            until: None,
+                            status: None,
+                            branch: None,
+                            event: None,
+                            actor: None,
+                        },
+                    );
+                    runs.take(last)
+                        .map_ok(move |run| {
+                            requests
+                                .clone()
+                                .artifacts(repository.clone(), run.id)
+                                .map_ok(move |artifact| (run.id, artifact))
+                        })
+                        .try_flatten()
+                        .boxed()
+                }
+                (None, None) => {
+                    return Err(StringErr(
+                        "Please provide either a --run-id or a --workflow".into(),
+                    )
+                    .into())
+                }
+            };
+            let mut artifacts = artifacts.take(config.limit());
+            if let Some(fields) = &config.fields {
+                let artifacts = artifacts.map_ok(|(_, artifact)| artifact);
+                return print_fields(&config, fields, Format::Tab, artifacts).await;
+            }
+            match config.output {
+                Output::Json => {
+                    return print_json(
+                        &artifacts
+                            .map_ok(|(_, artifact)| artifact)
+                            .try_collect::<Vec<_>>()
+                            .await?,
+                    )
+                }
+                Output::JsonLines => {
+                    return print_json_lines(artifacts.map_ok(|(_, artifact)| artifact)).await
+                }
+                Output::Text => (),
             }
+            let mut table = Table::new(Format::Tab).quiet(config.quiet);
+            table.header(&[&"Run", &"Name", &"ID", &"Size", &"Expired"])?;
+            while let Some((run_id, artifact)) = Pin::new(&mut artifacts).try_next().await? {
+                artifact_row(&mut table, run_id, &artifact)?;
+            }
+            table.flush()?;
         }
-        Artifacts::Delete {
-            repository,
+        Artifacts::Download {
+            run_id,
+            workflow,
+            latest_success,
             artifact_id,
+            name,
+            output,
         } => {
-            let client = Client::new();
-            let token = env::var("GITHUB_TOKEN")?;
-            let requests = Requests { client, token };
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let wanted = artifact_id
+                .map(|id| id.to_string())
+                .or_else(|| name.clone())
+                .unwrap_or_default();
+            let artifact = match (run_id, workflow) {
+                (Some(run_id), _) => requests
+                    .clone()
+                    .artifacts(repository, run_id)
+                    .try_filter(|artifact| {
+                        future::ready(selected(artifact, artifact_id, name.as_deref()))
+                    })
+                    .boxed()
+                    .try_next()
+                    .await?
+                    .ok_or_else(|| {
+                        StringErr(format!("Artifact {} not found for run {}", wanted, run_id))
+                    })?,
+                (None, Some(workflow)) => {
+                    let workflow = resolve_workflow(&requests, &repository, &workflow).await?;
+                    // runs are listed newest first, so the first artifact found is the latest
+                    requests
+                        .clone()
+                        .runs(
+                            repository.clone(),
+                            workflow.id.to_string(),
+                            RunFilter {
+                                since: Utc.timestamp(0, 0),
+                                until: None,
+                                status: if latest_success {
+                                    Some("success".into())
+                                } else {
+                                    None
+                                },
+                                branch: None,
+                                event: None,
+                                actor: None,
+                            },
+                        )
+                        .try_filter(|run| {
+                            future::ready(
+                                !latest_success || run.conclusion.as_deref() == Some("success"),
+                            )
+                        })
+                        .take(config.limit())
+                        .map_ok(|run| {
+                            requests
+                                .clone()
+                                .artifacts(repository.clone(), run.id)
+                                .try_filter(|artifact| {
+                                    future::ready(
+                                        !artifact.expired
+                                            && selected(artifact, artifact_id, name.as_deref()),
+                                    )
+                                })
+                        })
+                        .try_flatten()
+                        .boxed()
+                        .try_next()
+                        .await?
+                        .ok_or_else(|| {
+                            StringErr(format!(
+                                "No {}run of {} has an unexpired artifact {}",
+                                if latest_success { "successful " } else { "" },
+                                workflow.name,
+                                wanted
+                            ))
+                        })?
+                }
+                (None, None) => {
+                    return Err(StringErr(
+                        "Please provide either a --run-id or a --workflow".into(),
+                    )
+                    .into())
+                }
+            };
+            let output = output.unwrap_or_else(|| PathBuf::from(archive_name(&artifact)));
+            save_artifact(&requests, &artifact, &output, |downloaded| {
+                eprint!(
+                    "\rDownloading {} {}%",
+                    artifact.name,
+                    percent(downloaded, artifact.size_in_bytes)
+                );
+                let _ = stderr().flush();
+            })
+            .await?;
+            eprintln!();
+            println!("Artifact {} saved to {}", artifact.name, output.display());
+        }
+        Artifacts::DownloadAll { run_id, dir } => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let artifacts = requests
+                .clone()
+                .artifacts(repository, run_id)
+                .try_filter(|artifact| future::ready(!artifact.expired))
+                .try_collect::<Vec<_>>()
+                .await?;
+            if artifacts.is_empty() {
+                println!("Run {} has no unexpired artifacts", run_id);
+                return Ok(());
+            }
+            tokio::fs::create_dir_all(&dir).await?;
+            let failures = Rc::new(Cell::new(0));
+            stream::iter(artifacts)
+                .for_each_concurrent(Some(config.concurrency), |artifact| {
+                    let requests = requests.clone();
+                    let output = dir.join(archive_name(&artifact));
+                    let failures = failures.clone();
+                    async move {
+                        match save_artifact(&requests, &artifact, &output, |_| ()).await {
+                            Ok(size) => println!(
+                                "Artifact {} saved to {} {}",
+                                artifact.name.bold(),
+                                output.display(),
+                                human_size(size).dimmed()
+                            ),
+                            Err(err) => {
+                                failures.set(failures.get() + 1);
+                                println!(
+                                    "{}",
+                                    format!(
+                                        "Artifact {} could not be downloaded: {}",
+                                        artifact.name, err
+                                    )
+                                    .red()
+                                )
+                            }
+                        }
+                    }
+                })
+                .await;
+            if failures.get() > 0 {
+                return Err(StringErr(format!(
+                    "{} artifact(s) could not be downloaded",
+                    failures.get()
+                ))
+                .into());
+            }
+        }
+        Artifacts::Size {
+            workflow,
+            last,
+            top,
+        } => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let workflow = resolve_workflow(&requests, &repository, &workflow).await?;
+            let runs = requests
+                .clone()
+                .runs(
+                    repository.clone(),
+                    workflow.id.to_string(),
+                    RunFilter {
+                        since: Utc.timestamp(0, 0),
+                        until: None,
+                        status: None,
+                        branch: None,
+                        event: None,
+                        actor: None,
+                    },
+                )
+                .take(last)
+                .map_ok(|run| {
+                    requests
+                        .clone()
+                        .artifacts(repository.clone(), run.id)
+                        .try_filter(|artifact| future::ready(!artifact.expired))
+                        .try_collect::<Vec<_>>()
+                        .map_ok(move |artifacts| (run.id, artifacts))
+                })
+                .try_buffered(config.concurrency)
+                .try_collect::<Vec<_>>()
+                .await?;
+            let mut table = Table::new(Format::Tab).quiet(config.quiet);
+            table.header(&[&"Run", &"Artifacts", &"Size"])?;
+            for (run_id, artifacts) in &runs {
+                table.row(&[
+                    run_id,
+                    &artifacts.len(),
+                    &human_size(artifacts.iter().map(|a| a.size_in_bytes).sum()),
+                ])?;
+            }
+            table.flush()?;
+            let mut artifacts = runs
+                .into_iter()
+                .flat_map(|(run_id, artifacts)| {
+                    artifacts
+                        .into_iter()
+                        .map(move |artifact| (run_id, artifact))
+                })
+                .collect::<Vec<_>>();
+            if !config.quiet {
+                println!(
+                    "\nTotal {} across {} artifact(s)",
+                    human_size(artifacts.iter().map(|(_, a)| a.size_in_bytes).sum()).bold(),
+                    artifacts.len()
+                );
+            }
+            if let Some(top) = top {
+                artifacts.sort_by_key(|(_, artifact)| Reverse(artifact.size_in_bytes));
+                if !config.quiet {
+                    println!();
+                }
+                let mut table = Table::new(Format::Tab).quiet(config.quiet);
+                table.header(&[&"Run", &"Name", &"ID", &"Size"])?;
+                for (run_id, artifact) in artifacts.iter().take(top) {
+                    table.row(&[
+                        run_id,
+                        &artifact.name.bold(),
+                        &artifact.id,
+                        &human_size(artifact.size_in_bytes),
+                    ])?;
+                }
+                table.flush()?;
+            }
+        }
+        Artifacts::Delete { artifact_id, yes } => {
+            let repository = config.repository()?;
+            if !yes
+                && !config.dry_run
+                && !confirm(&format!(
+                    "Delete artifact {} of {}?",
+                    artifact_id, repository
+                ))?
+            {
+                return Ok(());
+            }
+            let requests = config.requests()?;
             requests.delete_artifact(repository, artifact_id).await?;
-            println!("Artifact {} is deleted", artifact_id);
+            config.changed(format!("Artifact {} is deleted", artifact_id));
+        }
+        Artifacts::Prune {
+            run_id,
+            expired_only,
+            older_than,
+        } => {
+            let repository = config.repository()?;
+            let created_before = match older_than {
+                Some(older_than) => Some(Utc::now() - chrono::Duration::from_std(older_than)?),
+                None => None,
+            };
+            let requests = config.requests()?;
+            let artifacts = requests
+                .clone()
+                .artifacts(repository.clone(), run_id)
+                .try_filter(|artifact| {
+                    future::ready(prunable(artifact, expired_only, created_before))
+                })
+                .try_collect::<Vec<_>>()
+                .await?;
+            if config.dry_run {
+                for artifact in &artifacts {
+                    println!(
+                        "Would delete {} {} {}",
+                        artifact.name.bold(),
+                        artifact.id,
+                        human_size(artifact.size_in_bytes).dimmed()
+                    );
+                }
+                println!(
+                    "{} artifact(s) would free {}",
+                    artifacts.len(),
+                    human_size(artifacts.iter().map(|a| a.size_in_bytes).sum())
+                );
+                return Ok(());
+            }
+            let freed = Rc::new(Cell::new(0));
+            let failures = Rc::new(Cell::new(0));
+            stream::iter(artifacts)
+                .for_each_concurrent(Some(config.concurrency), |artifact| {
+                    let requests = requests.clone();
+                    let repository = repository.clone();
+                    let freed = freed.clone();
+                    let failures = failures.clone();
+                    async move {
+                        match requests.delete_artifact(repository, artifact.id).await {
+                            Ok(()) => {
+                                freed.set(freed.get() + artifact.size_in_bytes);
+                                println!(
+                                    "Artifact {} is {}. {} freed",
+                                    artifact.name.bold(),
+                                    "deleted".green(),
+                                    human_size(freed.get())
+                                );
+                            }
+                            Err(err) => {
+                                failures.set(failures.get() + 1);
+                                println!(
+                                    "{}",
+                                    format!(
+                                        "Artifact {} could not be deleted: {}",
+                                        artifact.name, err
+                                    )
+                                    .red()
+                                )
+                            }
+                        }
+                    }
+                })
+                .await;
+            if failures.get() > 0 {
+                return Err(StringErr(format!(
+                    "{} artifact(s) could not be deleted",
+                    failures.get()
+                ))
+                .into());
+            }
         }
     }
 
     Ok(())
 }
+
+/// File name an artifact's archive is saved as by default. Path separators are replaced
+/// so that archives are always saved where asked
+fn archive_name(artifact: &Artifact) -> String {
+    format!("{}.zip", artifact.name.replace(['/', '\\'], "_"))
+}
+
+/// Downloads an artifact's archive to a file, calling `downloaded` with the number of
+/// bytes written so far as each chunk arrives. Returns the archive's size
+async fn save_artifact(
+    requests: &Requests,
+    artifact: &Artifact,
+    output: &Path,
+    mut downloaded: impl FnMut(usize),
+) -> Result<usize, Box<dyn Error>> {
+    let mut response = requests.download_artifact(artifact).await?;
+    let mut file = File::create(output).await?;
+    let mut written = 0;
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk).await?;
+        written += chunk.len();
+        downloaded(written);
+    }
+    file.flush().await?;
+    Ok(written)
+}
+
+/// Whether an artifact is the one asked for by id or by name
+fn selected(
+    artifact: &Artifact,
+    id: Option<usize>,
+    name: Option<&str>,
+) -> bool {
+    id.is_none_or(|id| artifact.id == id) && name.is_none_or(|name| artifact.name == name)
+}
+
+/// Percentage of an expected number of bytes, capped at 100
+fn percent(
+    downloaded: usize,
+    total: usize,
+) -> usize {
+    (downloaded * 100)
+        .checked_div(total)
+        .map_or(100, |percent| percent.min(100))
+}
+
+/// Whether an artifact matches prune's filters. Artifacts with an unknown creation
+/// time are never considered old enough to prune
+fn prunable(
+    artifact: &Artifact,
+    expired_only: bool,
+    created_before: Option<DateTime<Utc>>,
+) -> bool {
+    (!expired_only || artifact.expired)
+        && created_before.is_none_or(|before| {
+            artifact
+                .created_at
+                .is_some_and(|created_at| created_at < before)
+        })
+}
+
+/// Writes a listed artifact, along with the run which uploaded it, as a table row
+fn artifact_row(
+    table: &mut Table,
+    run_id: usize,
+    artifact: &Artifact,
+) -> io::Result<()> {
+    table.row(&[
+        &run_id,
+        &artifact.name.bold(),
+        &artifact.id,
+        &human_size(artifact.size_in_bytes),
+        &if artifact.expired {
+            "yes".red()
+        } else {
+            "no".normal()
+        },
+    ])
+}
+
+/// Formats a number of bytes using binary units, i.e. `4.2 MiB`
+pub(crate) fn human_size(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_parses_a_run_id_without_a_workflow() {
+        assert!(Artifacts::from_iter_safe(&["artifacts", "list", "--run-id", "1"]).is_ok());
+    }
+
+    #[test]
+    fn archive_name_replaces_path_separators() {
+        let artifact = |name: &str| Artifact {
+            id: 1,
+            name: name.into(),
+            size_in_bytes: 0,
+            archive_download_url: String::new(),
+            expired: false,
+            created_at: None,
+            workflow_run: None,
+        };
+        assert_eq!(archive_name(&artifact("coverage")), "coverage.zip");
+        assert_eq!(archive_name(&artifact("../logs\\a")), ".._logs_a.zip");
+    }
+
+    #[test]
+    fn artifact_rows_include_size_and_expiry() {
+        let mut out = Vec::new();
+        let mut table = Table::with_writer(Format::Csv, &mut out);
+        artifact_row(
+            &mut table,
+            1,
+            &Artifact {
+                id: 2,
+                name: "coverage".into(),
+                size_in_bytes: 4_404_019,
+                archive_download_url: String::new(),
+                expired: true,
+                created_at: None,
+                workflow_run: None,
+            },
+        )
+        .unwrap();
+        drop(table);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1,coverage,2,4.2 MiB,yes\n"
+        );
+    }
+
+    #[test]
+    fn percent_handles_unknown_and_overflowing_sizes() {
+        assert_eq!(percent(0, 0), 100);
+        assert_eq!(percent(50, 200), 25);
+        assert_eq!(percent(300, 200), 100);
+    }
+
+    #[test]
+    fn prunable_applies_every_filter() {
+        let artifact = |expired, created_at: Option<&str>| Artifact {
+            id: 1,
+            name: "dist".into(),
+            size_in_bytes: 1024,
+            archive_download_url: String::new(),
+            expired,
+            created_at: created_at.map(|at| at.parse().unwrap()),
+            workflow_run: None,
+        };
+        let before = Some("2020-03-01T00:00:00Z".parse().unwrap());
+        assert!(prunable(&artifact(false, None), false, None));
+        assert!(!prunable(&artifact(false, None), true, None));
+        assert!(prunable(
+            &artifact(true, Some("2020-02-01T00:00:00Z")),
+            true,
+            before
+        ));
+        assert!(!prunable(
+            &artifact(true, Some("2020-03-02T00:00:00Z")),
+            true,
+            before
+        ));
+        assert!(!prunable(&artifact(true, None), true, before));
+    }
+
+    #[test]
+    fn selected_matches_artifact_id_or_name() {
+        let artifact = Artifact {
+            id: 1,
+            name: "build".into(),
+            size_in_bytes: 1024,
+            archive_download_url: String::new(),
+            expired: false,
+            created_at: None,
+            workflow_run: None,
+        };
+        assert!(selected(&artifact, Some(1), None));
+        assert!(!selected(&artifact, Some(2), None));
+        assert!(selected(&artifact, None, Some("build")));
+        assert!(!selected(&artifact, None, Some("dist")));
+    }
+
+    #[test]
+    fn human_size_uses_binary_units() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KiB");
+        assert_eq!(human_size(4_404_019), "4.2 MiB");
+        assert_eq!(human_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
@@ -0,0 +1,99 @@
+//! On disk cache of api list responses, revalidated with their ETags so that
+//! unchanged pages don't count against the rate limit
+use serde::{Deserialize, Serialize};
+use std::{env, fs, path::PathBuf};
+
+/// A cached response, keyed by its url
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Entry {
+    pub etag: String,
+    /// Link to the next page of results, which responses to conditional requests may omit
+    pub next: Option<String>,
+    pub body: String,
+}
+
+/// A directory of cached responses. Failures to read or write entries are treated as cache misses
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf) -> Self {
+        Cache { dir }
+    }
+
+    /// Opens the cache under $XDG_CACHE_HOME, ~/.cache or, on Windows, %LOCALAPPDATA%
+    pub fn open() -> Option<Self> {
+        let base = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+        Some(Cache::new(base.join("actions-cli")))
+    }
+
+    pub fn get(
+        &self,
+        url: &str,
+    ) -> Option<Entry> {
+        serde_json::from_slice(&fs::read(self.path(url)).ok()?).ok()
+    }
+
+    pub fn put(
+        &self,
+        url: &str,
+        entry: &Entry,
+    ) {
+        let path = self.path(url);
+        // entries are renamed into place so that concurrent commands never read partial writes
+        let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+        let written = fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&temp, serde_json::to_vec(entry)?))
+            .and_then(|_| fs::rename(&temp, &path));
+        if written.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+    }
+
+    fn path(
+        &self,
+        url: &str,
+    ) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.json", fnv1a(url.as_bytes())))
+    }
+}
+
+/// A hash which, unlike std's default hasher, is stable across releases so that
+/// entries outlive upgrades
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_stores_entries_by_url() {
+        let cache =
+            Cache::new(env::temp_dir().join(format!("actions-cache-{}", std::process::id())));
+        let entry = Entry {
+            etag: "W/\"abc\"".into(),
+            next: None,
+            body: "[]".into(),
+        };
+        cache.put("https://api.github.com/repos/a/b/actions/workflows", &entry);
+        assert_eq!(
+            cache.get("https://api.github.com/repos/a/b/actions/workflows"),
+            Some(entry)
+        );
+        assert_eq!(
+            cache.get("https://api.github.com/repos/a/c/actions/workflows"),
+            None
+        );
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+}
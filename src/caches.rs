@@ -0,0 +1,78 @@
+use crate::{
+    artifacts::human_size,
+    confirm,
+    format::{Format, Table},
+    print_fields, print_json, print_json_lines,
+    runs::format_time,
+    Config, Output,
+};
+use colored::Colorize;
+use futures::{StreamExt, TryStreamExt};
+use std::{error::Error, pin::Pin};
+use structopt::StructOpt;
+
+/// 🗄️ Manage actions caches
+#[derive(StructOpt, Debug)]
+pub enum Caches {
+    /// List a repository's actions caches, most recently used first
+    List {},
+    /// Delete an actions cache
+    Delete {
+        /// Id of cache to delete
+        #[structopt(short, long)]
+        cache_id: usize,
+        /// Delete without asking for confirmation
+        #[structopt(short, long)]
+        yes: bool,
+    },
+}
+
+pub async fn caches(
+    args: Caches,
+    config: Config,
+) -> Result<(), Box<dyn Error>> {
+    match args {
+        Caches::List {} => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let mut caches = requests.caches(repository).take(config.limit()).boxed();
+            if let Some(fields) = &config.fields {
+                return print_fields(&config, fields, Format::Tab, caches).await;
+            }
+            match config.output {
+                Output::Json => return print_json(&caches.try_collect::<Vec<_>>().await?),
+                Output::JsonLines => return print_json_lines(caches).await,
+                Output::Text => (),
+            }
+            let mut table = Table::new(Format::Tab).quiet(config.quiet);
+            table.header(&[&"Key", &"ID", &"Ref", &"Size", &"Last Accessed"])?;
+            while let Some(cache) = Pin::new(&mut caches).try_next().await? {
+                table.row(&[
+                    &cache.key.bold(),
+                    &cache.id,
+                    &cache.git_ref.dimmed(),
+                    &human_size(cache.size_in_bytes),
+                    &cache
+                        .last_accessed_at
+                        .map(|at| format_time(at, config.date_format.as_deref(), false))
+                        .unwrap_or_default(),
+                ])?;
+            }
+            table.flush()?;
+        }
+        Caches::Delete { cache_id, yes } => {
+            let repository = config.repository()?;
+            if !yes
+                && !config.dry_run
+                && !confirm(&format!("Delete cache {} of {}?", cache_id, repository))?
+            {
+                return Ok(());
+            }
+            let requests = config.requests()?;
+            requests.delete_cache(repository, cache_id).await?;
+            config.changed(format!("Cache {} is deleted", cache_id));
+        }
+    }
+
+    Ok(())
+}
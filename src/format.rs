@@ -0,0 +1,529 @@
+//! Tabular output shared by listing commands
+use crate::StringErr;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    env,
+    error::Error,
+    fmt::Display,
+    io::{self, stdout, Write},
+    process::{Child, Command, Stdio},
+    str::FromStr,
+};
+use tabwriter::TabWriter;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Format {
+    #[default]
+    Tab,
+    Csv,
+    Markdown,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Format::Csv),
+            "tab" => Ok(Format::Tab),
+            "md" | "markdown" => Ok(Format::Markdown),
+            other => Err(format!(
+                "{} is not a supported format. try 'csv', 'tab' or 'md' instead",
+                other
+            )),
+        }
+    }
+}
+
+/// Names of the fields of listed items to output, in order, i.e. `id,name,conclusion`.
+/// Fields are named as in json output
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fields(Vec<String>);
+
+impl FromStr for Fields {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>();
+        if fields.is_empty() {
+            return Err(format!(
+                "{} names no fields. try a comma separated list such as id,name instead",
+                s
+            ));
+        }
+        Ok(Fields(fields))
+    }
+}
+
+impl Fields {
+    pub fn names(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Selects these fields of an item's json representation, in order. Fails listing
+    /// the item's fields when one of these is unknown
+    pub fn select<T: Serialize>(
+        &self,
+        item: &T,
+    ) -> Result<Vec<(String, Value)>, Box<dyn Error>> {
+        let object = match serde_json::to_value(item)? {
+            Value::Object(object) => object,
+            _ => return Err(StringErr("Only objects have fields to select".into()).into()),
+        };
+        self.0
+            .iter()
+            .map(|name| match object.get(name) {
+                Some(value) => Ok((name.clone(), value.clone())),
+                None => Err(StringErr(format!(
+                    "{} is not a known field. try one of {} instead",
+                    name,
+                    object
+                        .keys()
+                        .map(String::as_str)
+                        .collect::<BTreeSet<_>>()
+                        .into_iter()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+                .into()),
+            })
+            .collect()
+    }
+
+    /// Selects these fields as a json object
+    pub fn object<T: Serialize>(
+        &self,
+        item: &T,
+    ) -> Result<Value, Box<dyn Error>> {
+        Ok(Value::Object(self.select(item)?.into_iter().collect()))
+    }
+
+    /// Selects these fields as table cells. Strings are unquoted and nulls left empty
+    pub fn cells<T: Serialize>(
+        &self,
+        item: &T,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(self
+            .select(item)?
+            .into_iter()
+            .map(|(_, value)| match value {
+                Value::String(value) => value,
+                Value::Null => String::new(),
+                value => value.to_string(),
+            })
+            .collect())
+    }
+}
+
+/// Writes rows as aligned columns, as csv or as a markdown table. Commands write to
+/// stdout while tests render into a buffer
+pub struct Table<'a> {
+    format: Format,
+    writer: Box<dyn Write + 'a>,
+    rows: usize,
+    quiet: bool,
+}
+
+impl Table<'static> {
+    /// Creates a table for a given format. Csv and markdown output are never colored so
+    /// that they may be imported or pasted as is
+    pub fn new(format: Format) -> Self {
+        Self::with_pager(format, false)
+    }
+
+    /// Creates a table which, when `pager` is set, pipes aligned columns through `$PAGER`.
+    /// The pager is waited on once the table is dropped, so nothing else should be
+    /// printed to stdout while the table is in use. Use `line` instead
+    pub fn with_pager(
+        format: Format,
+        pager: bool,
+    ) -> Self {
+        let pager = if pager && format == Format::Tab {
+            Pager::spawn()
+        } else {
+            None
+        };
+        match pager {
+            Some(pager) => Table::with_writer(format, pager),
+            None => Table::with_writer(format, stdout()),
+        }
+    }
+}
+
+impl<'a> Table<'a> {
+    /// Creates a table which writes to `writer`, i.e. a buffer
+    pub fn with_writer(
+        format: Format,
+        writer: impl Write + 'a,
+    ) -> Self {
+        let writer: Box<dyn Write + 'a> = match format {
+            Format::Tab => Box::new(TabWriter::new(writer)),
+            Format::Csv | Format::Markdown => Box::new(writer),
+        };
+        Table {
+            format,
+            writer,
+            rows: 0,
+            quiet: false,
+        }
+    }
+
+    /// Leaves out header rows when `quiet` is set, so that only the listed rows are written
+    pub fn quiet(
+        mut self,
+        quiet: bool,
+    ) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Writes a row naming the columns of the rows that follow. Quiet tables leave headers
+    /// out, except for markdown tables which require one
+    pub fn header(
+        &mut self,
+        fields: &[&dyn Display],
+    ) -> io::Result<()> {
+        if self.quiet && self.format != Format::Markdown {
+            return Ok(());
+        }
+        self.row(fields)
+    }
+
+    /// Writes a header of plain cells, i.e. the names of selected `Fields`
+    pub fn header_cells(
+        &mut self,
+        cells: &[String],
+    ) -> io::Result<()> {
+        self.header(
+            &cells
+                .iter()
+                .map(|cell| cell as &dyn Display)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Writes a row of fields. The first row of a markdown table is its header
+    pub fn row(
+        &mut self,
+        fields: &[&dyn Display],
+    ) -> io::Result<()> {
+        let fields = fields
+            .iter()
+            .map(|field| self.plain(field))
+            .collect::<Vec<_>>();
+        let count = fields.len();
+        match self.format {
+            Format::Tab => writeln!(self.writer, "{}", fields.join("\t"))?,
+            Format::Csv => write_csv_row(
+                &mut self.writer,
+                &fields.iter().map(String::as_str).collect::<Vec<_>>(),
+            )?,
+            Format::Markdown => writeln!(self.writer, "{}", markdown_row(fields))?,
+        }
+        if self.format == Format::Markdown && self.rows == 0 {
+            writeln!(self.writer, "{}", markdown_row(vec!["---".into(); count]))?;
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Writes a row of plain cells, i.e. the selected `Fields` of an item
+    pub fn cells(
+        &mut self,
+        cells: &[String],
+    ) -> io::Result<()> {
+        self.row(
+            &cells
+                .iter()
+                .map(|cell| cell as &dyn Display)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Writes a line as is, i.e. a summary following the table's rows
+    pub fn line(
+        &mut self,
+        line: &dyn Display,
+    ) -> io::Result<()> {
+        let line = self.plain(line);
+        writeln!(self.writer, "{}", line)
+    }
+
+    /// Renders a field, leaving out the colors of csv and markdown output
+    fn plain(
+        &self,
+        field: &dyn Display,
+    ) -> String {
+        match self.format {
+            Format::Tab => field.to_string(),
+            Format::Csv | Format::Markdown => strip_colors(&field.to_string()),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl Drop for Table<'_> {
+    /// Tables dropped early, i.e. when interrupted, still print the complete rows they were given
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+/// A pager process, i.e. `less -R`, that output is written to
+struct Pager(Child);
+
+impl Pager {
+    /// Spawns `$PAGER`, or `less -R` which keeps colors when no PAGER is set. Returns nothing
+    /// when PAGER is empty or `cat`, or the pager can't be started
+    fn spawn() -> Option<Self> {
+        let pager = env::var("PAGER").ok();
+        let mut args = pager_command(pager.as_deref())?.into_iter();
+        Command::new(args.next()?)
+            .args(args)
+            .stdin(Stdio::piped())
+            .spawn()
+            .ok()
+            .map(Pager)
+    }
+}
+
+impl Write for Pager {
+    fn write(
+        &mut self,
+        buf: &[u8],
+    ) -> io::Result<usize> {
+        match self.0.stdin.as_mut().map(|stdin| stdin.write(buf)) {
+            // the pager was quit before all output was read
+            Some(Err(err)) if err.kind() == io::ErrorKind::BrokenPipe => Ok(buf.len()),
+            Some(result) => result,
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.0.stdin.as_mut().map(Write::flush) {
+            Some(Err(err)) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            Some(result) => result,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        // closing stdin lets the pager know output is done
+        drop(self.0.stdin.take());
+        let _ = self.0.wait();
+    }
+}
+
+/// The program and arguments of a PAGER
+fn pager_command(pager: Option<&str>) -> Option<Vec<&str>> {
+    match pager.map(str::trim) {
+        None => Some(vec!["less", "-R"]),
+        Some("") | Some("cat") => None,
+        Some(pager) => Some(pager.split_whitespace().collect()),
+    }
+}
+
+/// Writes a csv record, escaping fields as RFC 4180 describes so that commas, quotes and
+/// line breaks within them are imported as is
+fn write_csv_row(
+    out: &mut impl Write,
+    fields: &[&str],
+) -> io::Result<()> {
+    let record = fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",");
+    writeln!(out, "{}", record)
+}
+
+/// Quotes a csv field when it contains a delimiter, quote or line break
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Removes the ANSI escape sequences that color text, i.e. `\x1b[31m`
+fn strip_colors(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // a sequence ends with its first letter, i.e. the m of a color
+            chars.find(char::is_ascii_alphabetic);
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// Writes fields as a markdown table row, escaping pipes and replacing line breaks,
+/// which would otherwise end the row, with spaces
+fn markdown_row(fields: impl IntoIterator<Item = String>) -> String {
+    let cells = fields
+        .into_iter()
+        .map(|field| field.trim().replace('|', "\\|").replace(['\r', '\n'], " "))
+        .collect::<Vec<_>>();
+    format!("| {} |", cells.join(" | "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_field_quotes_only_when_needed() {
+        assert_eq!(
+            csv_field(".github/workflows/ci.yml"),
+            ".github/workflows/ci.yml"
+        );
+        assert_eq!(csv_field("build, test"), "\"build, test\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn write_csv_row_escapes_commas_quotes_and_line_breaks() {
+        let mut out = Vec::new();
+        write_csv_row(
+            &mut out,
+            &["ci, nightly", "feature/\"quoted\"", "one\ntwo", "plain", ""],
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\"ci, nightly\",\"feature/\"\"quoted\"\"\",\"one\ntwo\",plain,\n"
+        );
+    }
+
+    #[test]
+    fn markdown_row_escapes_pipes_and_line_breaks() {
+        assert_eq!(
+            markdown_row(vec!["CI".into(), ".github/workflows/ci.yml".into()]),
+            "| CI | .github/workflows/ci.yml |"
+        );
+        assert_eq!(
+            markdown_row(vec!["build | test".into(), "one\ntwo".into()]),
+            "| build \\| test | one two |"
+        );
+    }
+
+    #[test]
+    fn tables_render_each_format() {
+        let render = |format| {
+            let mut out = Vec::new();
+            let mut table = Table::with_writer(format, &mut out);
+            table.row(&[&"Workflow", &"Path"]).unwrap();
+            table.row(&[&"CI, nightly", &"ci.yml"]).unwrap();
+            table.line(&"Total 1").unwrap();
+            drop(table);
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            render(Format::Tab),
+            "Workflow     Path\nCI, nightly  ci.yml\nTotal 1\n"
+        );
+        assert_eq!(
+            render(Format::Csv),
+            "Workflow,Path\n\"CI, nightly\",ci.yml\nTotal 1\n"
+        );
+        assert_eq!(
+            render(Format::Markdown),
+            "| Workflow | Path |\n| --- | --- |\n| CI, nightly | ci.yml |\nTotal 1\n"
+        );
+    }
+
+    #[test]
+    fn csv_and_markdown_tables_are_never_colored() {
+        let render = |format| {
+            let mut out = Vec::new();
+            let mut table = Table::with_writer(format, &mut out);
+            table
+                .row(&[&"\x1b[1mCI\x1b[0m", &"\x1b[32msuccess\x1b[0m"])
+                .unwrap();
+            table.line(&"\x1b[2mTotal 1\x1b[0m").unwrap();
+            drop(table);
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(render(Format::Csv), "CI,success\nTotal 1\n");
+        assert_eq!(
+            render(Format::Markdown),
+            "| CI | success |\n| --- | --- |\nTotal 1\n"
+        );
+    }
+
+    #[test]
+    fn quiet_tables_leave_out_headers_except_in_markdown() {
+        let render = |format| {
+            let mut out = Vec::new();
+            let mut table = Table::with_writer(format, &mut out).quiet(true);
+            table.header(&[&"Workflow", &"Path"]).unwrap();
+            table.row(&[&"CI", &"ci.yml"]).unwrap();
+            drop(table);
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(render(Format::Csv), "CI,ci.yml\n");
+        assert_eq!(
+            render(Format::Markdown),
+            "| Workflow | Path |\n| --- | --- |\n| CI | ci.yml |\n"
+        );
+    }
+
+    #[test]
+    fn pager_command_defaults_to_less() {
+        assert_eq!(pager_command(None), Some(vec!["less", "-R"]));
+        assert_eq!(pager_command(Some("more -d")), Some(vec!["more", "-d"]));
+        assert_eq!(pager_command(Some("cat")), None);
+        assert_eq!(pager_command(Some(" ")), None);
+    }
+
+    #[test]
+    fn fields_select_fields_in_order() {
+        let fields = "conclusion, id".parse::<Fields>().unwrap();
+        let run = serde_json::json!({ "id": 1, "name": "CI", "conclusion": null });
+        assert_eq!(fields.cells(&run).unwrap(), vec!["", "1"]);
+        assert_eq!(
+            serde_json::to_string(&fields.object(&run).unwrap()).unwrap(),
+            r#"{"conclusion":null,"id":1}"#
+        );
+        assert_eq!(
+            "status"
+                .parse::<Fields>()
+                .unwrap()
+                .cells(&run)
+                .unwrap_err()
+                .to_string(),
+            "status is not a known field. try one of conclusion, id, name instead"
+        );
+        assert!(",".parse::<Fields>().is_err());
+    }
+
+    #[test]
+    fn format_parses_supported_formats() {
+        assert_eq!("csv".parse::<Format>(), Ok(Format::Csv));
+        assert_eq!("tab".parse::<Format>(), Ok(Format::Tab));
+        assert_eq!("md".parse::<Format>(), Ok(Format::Markdown));
+        assert!("xml".parse::<Format>().is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! Detection of the GitHub repository of the current git checkout
+use crate::token::gh_host;
+use std::process::{Command, Stdio};
+
+/// Detects the owner/repo of the current directory's `origin` remote, if any. Only remotes
+/// hosted on github.com or the GitHub Enterprise Server of `api_url` are GitHub repositories
+pub fn detect_repository(api_url: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["remote", "get-url", "origin"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let host = gh_host(api_url)?;
+    parse_remote(
+        String::from_utf8(output.stdout).ok()?.trim(),
+        &["github.com", &host],
+    )
+}
+
+/// Extracts owner/repo from an ssh (`git@github.com:owner/repo.git`,
+/// `ssh://git@github.com/owner/repo`) or https (`https://github.com/owner/repo.git`) remote
+/// url, when it's on one of `hosts`
+fn parse_remote(
+    url: &str,
+    hosts: &[&str],
+) -> Option<String> {
+    let (authority, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        None => url.split_once(':')?,
+    };
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    if !hosts.contains(&host) {
+        return None;
+    }
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    match path.split('/').collect::<Vec<_>>().as_slice() {
+        [owner, repo] if !owner.is_empty() && !repo.is_empty() => {
+            Some(format!("{}/{}", owner, repo))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_remote_supports_ssh_and_https_urls() {
+        for url in &[
+            "git@github.com:softprops/actions.git",
+            "git@github.com:softprops/actions",
+            "ssh://git@github.com/softprops/actions.git",
+            "https://github.com/softprops/actions.git",
+            "https://github.com/softprops/actions/",
+            "https://token@github.example.com/softprops/actions",
+        ] {
+            assert_eq!(
+                parse_remote(url, &["github.com", "github.example.com"]),
+                Some("softprops/actions".to_string()),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn parse_remote_rejects_non_repository_paths() {
+        let hosts = ["github.com"];
+        assert_eq!(parse_remote("https://github.com/softprops", &hosts), None);
+        assert_eq!(parse_remote("/home/me/actions", &hosts), None);
+        assert_eq!(
+            parse_remote("https://gitlab.com/group/sub/repo.git", &hosts),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_remote_rejects_other_hosts() {
+        let hosts = ["github.com"];
+        assert_eq!(parse_remote("git@gitlab.com:group/repo.git", &hosts), None);
+        assert_eq!(
+            parse_remote("https://bitbucket.org/team/repo", &hosts),
+            None
+        );
+        assert_eq!(
+            parse_remote("ssh://git@github.com:22/softprops/actions", &hosts),
+            Some("softprops/actions".to_string())
+        );
+    }
+}
@@ -1,23 +1,56 @@
+use crate::{
+    cache::{Cache, Entry},
+    throttle::Throttle,
+    token::App,
+};
 use chrono::{DateTime, Utc};
 use futures::{
-    stream,
-    stream::{Stream, StreamExt},
+    future, stream,
+    stream::{Stream, StreamExt, TryStreamExt},
 };
+use humantime::format_duration;
 use hyperx::header::{Header, Link, RelationType};
-use reqwest::{header::LINK, RequestBuilder, Response};
+use indicatif::ProgressBar;
+use log::debug;
+use reqwest::{
+    header::{ETAG, IF_NONE_MATCH, LINK},
+    Method, RequestBuilder, Response, StatusCode,
+};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::{collections::BTreeMap, error::Error, time::Duration};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt, io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{sync::Mutex, time::delay_for};
 use url::form_urlencoded::byte_serialize as urlencode;
 
 #[derive(Debug, Deserialize, Clone)]
 struct CodeSearch {
+    total_count: usize,
     incomplete_results: bool,
     items: Vec<CodeSearchItem>,
 }
 
+/// Code search only returns this many results, however many matched
+const CODE_SEARCH_LIMIT: usize = 1000;
+
+impl CodeSearch {
+    /// Whether some matches are missing from the results, because the search
+    /// timed out or because there were more matches than code search returns
+    fn incomplete(&self) -> bool {
+        self.incomplete_results || self.total_count > CODE_SEARCH_LIMIT
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 struct CodeSearchItem {
-    pub name: String,
     pub path: String,
     pub repository: Repository,
 }
@@ -27,10 +60,16 @@ struct Repository {
     pub full_name: String,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Repo {
     pub full_name: String,
     pub workflows: Vec<String>,
+    /// Whether the repository is archived, when its details were fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived: Option<bool>,
+    /// Whether the repository is a fork, when its details were fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fork: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -40,24 +79,20 @@ pub struct Jobs {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Job {
-    pub id: usize,
     pub html_url: String,
     pub status: String,
     pub conclusion: Option<String>,
-    pub started_at: Option<String>,
-    pub completed_at: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
     pub name: String,
-    pub steps: Vec<Step>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
-pub struct Step {
-    pub name: String,
-    pub status: String,
-    pub conclusion: Option<String>,
-    pub number: usize,
-    pub started_at: Option<String>,
-    pub completed_at: Option<String>,
+impl Job {
+    /// Time the job took to complete, if it has completed
+    pub fn duration(&self) -> Option<Duration> {
+        let started_at = self.started_at?;
+        (self.completed_at? - started_at).to_std().ok()
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -65,15 +100,59 @@ pub struct Artifacts {
     pub artifacts: Vec<Artifact>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Artifact {
     pub id: usize,
     pub name: String,
     pub size_in_bytes: usize,
     pub archive_download_url: String,
+    pub expired: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub workflow_run: Option<ArtifactRun>,
+}
+
+/// The workflow run which uploaded an artifact
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ArtifactRun {
+    pub id: usize,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ActionsCaches {
+    pub actions_caches: Vec<ActionsCache>,
+}
+
+/// An entry of a repository's actions cache, saved by a workflow's cache action
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ActionsCache {
+    pub id: usize,
+    pub key: String,
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    pub size_in_bytes: usize,
+    pub last_accessed_at: Option<DateTime<Utc>>,
+}
+
+/// A file's contents, base64 encoded
+#[derive(Debug, Deserialize)]
+struct Contents {
+    content: String,
+}
+
+/// Error body returned by the API for unsuccessful requests
+#[derive(Debug, Deserialize, Clone)]
+struct ClientError {
+    message: String,
 }
 
+/// Optional body of a run re-run response
 #[derive(Debug, Deserialize, Clone)]
+struct Rerun {
+    html_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Key {
     pub key_id: String,
     pub key: String,
@@ -83,6 +162,52 @@ pub struct Key {
 pub struct SecretValue {
     pub encrypted_value: String,
     pub key_id: String,
+    /// Which organization repositories may access the secret: `all`, `private` or `selected`.
+    /// Only applies to organization secrets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
+    /// Repositories that may access an organization secret with `selected` visibility
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_repository_ids: Option<Vec<u64>>,
+}
+
+/// Owner of a set of secrets
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecretScope {
+    /// A repository in the form owner/repo
+    Repo(String),
+    /// An organization
+    Org(String),
+    /// A deployment environment of the repository with this id
+    Environment { repository_id: u64, name: String },
+}
+
+impl SecretScope {
+    /// Api path of this scope's secrets
+    fn path(&self) -> String {
+        match self {
+            SecretScope::Repo(repo) => format!("/repos/{repo}/actions/secrets", repo = repo),
+            SecretScope::Org(org) => format!("/orgs/{org}/actions/secrets", org = org),
+            SecretScope::Environment {
+                repository_id,
+                name,
+            } => format!(
+                "/repositories/{repository_id}/environments/{name}/secrets",
+                repository_id = repository_id,
+                name = urlencode(name.as_bytes()).collect::<String>()
+            ),
+        }
+    }
+}
+
+/// Details of a repository which code search results leave out
+#[derive(Debug, Deserialize)]
+pub struct RepoDetails {
+    pub id: u64,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub fork: bool,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -90,9 +215,13 @@ pub struct Secrets {
     pub secrets: Vec<Secret>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Secret {
     pub name: String,
+    /// Only present for organization secrets
+    pub visibility: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -100,7 +229,7 @@ pub struct Workflows {
     pub workflows: Vec<Workflow>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Workflow {
     pub id: usize,
     pub name: String,
@@ -108,12 +237,39 @@ pub struct Workflow {
     pub path: String,
 }
 
+impl Workflow {
+    /// Whether the workflow runs on its triggers, as opposed to being disabled or deleted
+    pub fn active(&self) -> bool {
+        self.state == "active"
+    }
+
+    /// The workflow's file name, i.e. `ci.yml`
+    pub fn filename(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+/// Body of a workflow dispatch event
+#[derive(Debug, Serialize, Clone)]
+pub struct Dispatch {
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    pub inputs: BTreeMap<String, String>,
+}
+
+/// A repository_dispatch event, which triggers workflows listening for its event type
+#[derive(Debug, Serialize)]
+pub struct RepositoryDispatch {
+    pub event_type: String,
+    pub client_payload: Value,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Runs {
     pub workflow_runs: Vec<Run>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Run {
     pub id: usize,
     pub head_branch: String,
@@ -128,17 +284,87 @@ pub struct Run {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub html_url: String,
+    /// User whose action first triggered the run
+    #[serde(default)]
+    pub actor: Option<User>,
+    /// Name of the run's workflow, which the api may leave out
+    #[serde(default)]
+    pub name: Option<String>,
+    pub workflow_id: usize,
+}
+
+/// A GitHub user
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct User {
+    pub login: String,
 }
 
 impl Run {
+    /// Time between the run's creation and its last update. Clock skew can leave a run
+    /// updated before it was created, in which case this is zero
     pub fn duration(&self) -> Duration {
-        (self.updated_at - self.created_at).to_std().unwrap()
+        (self.updated_at - self.created_at)
+            .to_std()
+            .unwrap_or_default()
+    }
+
+    /// Name of the run's workflow, or its workflow id when the run has no name
+    pub fn workflow_name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| self.workflow_id.to_string())
+    }
+
+    /// Whether the run was created at or after `since` and strictly before `until`
+    pub fn created_within(
+        &self,
+        since: DateTime<Utc>,
+        until: Option<DateTime<Utc>>,
+    ) -> bool {
+        self.created_at >= since && until.is_none_or(|until| self.created_at < until)
+    }
+}
+
+/// Criteria for listing workflow runs
+#[derive(Debug, Clone)]
+pub struct RunFilter {
+    /// Only runs created at or after this time
+    pub since: DateTime<Utc>,
+    /// Only runs created before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only runs with this status
+    pub status: Option<String>,
+    /// Only runs for this branch, ignoring case
+    pub branch: Option<String>,
+    /// Only runs triggered by this event
+    pub event: Option<String>,
+    /// Only runs triggered by this user, ignoring case
+    pub actor: Option<String>,
+}
+
+impl RunFilter {
+    pub fn matches(
+        &self,
+        run: &Run,
+    ) -> bool {
+        run.created_within(self.since, self.until)
+            && self
+                .branch
+                .as_ref()
+                .is_none_or(|branch| run.head_branch.eq_ignore_ascii_case(branch))
+            && self.event.as_ref().is_none_or(|event| &run.event == event)
+            && self.actor.as_ref().is_none_or(|login| {
+                run.actor
+                    .as_ref()
+                    .is_some_and(|actor| actor.login.eq_ignore_ascii_case(login))
+            })
     }
 }
 
+/// Billable time of a workflow during the current billing cycle, by runner os
 #[derive(Debug, Deserialize, Clone)]
-pub struct Usage {
-    pub billable: std::collections::BTreeMap<String, Timing>,
+pub struct WorkflowUsage {
+    pub billable: BTreeMap<String, Timing>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -146,7 +372,7 @@ pub struct Timing {
     pub total_ms: u64,
 }
 
-impl Usage {
+impl WorkflowUsage {
     fn duration(
         &self,
         image: impl AsRef<str>,
@@ -164,27 +390,326 @@ impl Usage {
     }
 }
 
+/// An account's GitHub Actions minutes during the current billing cycle
+#[derive(Debug, Deserialize, Clone)]
+pub struct ActionsBilling {
+    pub total_minutes_used: u64,
+    pub included_minutes: u64,
+}
+
+/// Errors that may occur while fetching api results, distinguishing failures a caller
+/// may handle, i.e. missing resources, from those it can't
+#[derive(Debug)]
+pub enum ActionsError {
+    /// The token was rejected or lacks the permissions required
+    Auth(String),
+    /// The token is valid but lacks a fine-grained permission the request requires. GitHub
+    /// names the permissions which would have been accepted, i.e. `actions=read`, when it can
+    MissingPermission {
+        message: String,
+        accepted: Option<String>,
+    },
+    /// The requested resource does not exist or is not visible to the token
+    NotFound(String),
+    /// The rate limit was exceeded and retries were exhausted, or would have waited too long.
+    /// Requests may be made again after `retry_after`
+    RateLimited { retry_after: Duration },
+    /// Any other unsuccessful api response
+    Api { status: StatusCode, message: String },
+    /// The request could not be sent or its response could not be read
+    Http(reqwest::Error),
+    /// The response was not in the format expected
+    Decode(Box<dyn Error + Send + Sync>),
+}
+
+impl ActionsError {
+    /// Whether the request may succeed when sent again, as with server errors and
+    /// failed connections, unlike rejected credentials or missing resources
+    fn recoverable(&self) -> bool {
+        match self {
+            ActionsError::Api { status, .. } => status.is_server_error(),
+            ActionsError::Http(err) => is_retryable(err, Retry::Always),
+            _ => false,
+        }
+    }
+
+    async fn from_response(response: Response) -> Self {
+        let url = response.url().to_string();
+        let status = response.status();
+        let retry_after = rate_limit_delay(&response, Utc::now());
+        let accepted = response
+            .headers()
+            .get("x-accepted-github-permissions")
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| !value.is_empty())
+            .map(String::from);
+        let message = client_error(response).await;
+        match (status, retry_after) {
+            (_, Some(retry_after)) => ActionsError::RateLimited { retry_after },
+            (StatusCode::FORBIDDEN, _)
+                if accepted.is_some() || message.starts_with("Resource not accessible by") =>
+            {
+                ActionsError::MissingPermission { message, accepted }
+            }
+            (StatusCode::UNAUTHORIZED, _) | (StatusCode::FORBIDDEN, _) => {
+                ActionsError::Auth(message)
+            }
+            (StatusCode::NOT_FOUND, _) => ActionsError::NotFound(url),
+            (status, _) => ActionsError::Api { status, message },
+        }
+    }
+}
+
+impl From<reqwest::Error> for ActionsError {
+    fn from(err: reqwest::Error) -> Self {
+        ActionsError::Http(err)
+    }
+}
+
+impl Error for ActionsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ActionsError::Http(err) => Some(err),
+            ActionsError::Decode(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ActionsError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            ActionsError::Auth(message) => write!(
+                f,
+                "authentication failed: {}. Check that your GITHUB_TOKEN is valid and has access",
+                message
+            ),
+            ActionsError::MissingPermission { message, accepted } => {
+                write!(f, "the token lacks a required permission: {}", message)?;
+                match accepted {
+                    Some(accepted) => write!(f, ". Accepted permissions: {}", accepted),
+                    None => Ok(()),
+                }
+            }
+            ActionsError::NotFound(url) => write!(f, "{} was not found", url),
+            ActionsError::RateLimited { retry_after } => write!(
+                f,
+                "rate limit exceeded. Try again in {}",
+                format_duration(*retry_after)
+            ),
+            ActionsError::Api { status, message } => {
+                write!(f, "request failed with status {}: {}", status, message)
+            }
+            ActionsError::Http(err) => write!(f, "{}", err),
+            ActionsError::Decode(err) => write!(f, "unexpected response: {}", err),
+        }
+    }
+}
+
+/// Default base url for GitHub's public API
+pub const DEFAULT_BASE_URL: &str = "https://api.github.com";
+
+/// User-Agent sent unless one is configured
+pub const DEFAULT_USER_AGENT: &str = env!("CARGO_PKG_NAME");
+
+/// Version of the REST API requests are pinned to unless another is configured
+pub const DEFAULT_API_VERSION: &str = "2022-11-28";
+
+/// How long to wait for a connection to be established
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long archive downloads may take. Archives can be large so they are given
+/// much longer than other requests
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// How long a request may wait in total on rate limits before its rate limited
+/// response is returned as is
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// How many more times a page following the first is fetched after a recoverable failure,
+/// so that a single failure doesn't cut a long listing short
+const NEXT_PAGE_RETRIES: usize = 3;
+
+/// Builds an http client whose requests fail after `timeout`. Proxies are
+/// configured from HTTPS_PROXY and HTTP_PROXY env variables
+pub fn build_client(timeout: Duration) -> Result<reqwest::Client, reqwest::Error> {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(timeout)
+        .build()
+}
+
+/// How requests are authenticated
+#[derive(Clone)]
+pub enum Credentials {
+    /// A personal access or actions token
+    Token(String),
+    /// A GitHub App installation. Its access token is minted on first use and
+    /// shared by clones until shortly before it expires
+    App {
+        app: App,
+        installation_token: Arc<Mutex<Option<InstallationToken>>>,
+    },
+}
+
+impl Credentials {
+    pub fn app(app: App) -> Self {
+        Credentials::App {
+            app,
+            installation_token: Arc::default(),
+        }
+    }
+}
+
+/// An access token for a GitHub App installation, valid for an hour
+#[derive(Debug, Deserialize, Clone)]
+pub struct InstallationToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl InstallationToken {
+    /// Whether the token may still be used, leaving a margin for requests in flight
+    fn valid_at(
+        &self,
+        now: DateTime<Utc>,
+    ) -> bool {
+        now + chrono::Duration::minutes(5) < self.expires_at
+    }
+}
+
 /// A GitHub actions client for executing requests
 #[derive(Clone)]
 pub struct Requests {
     pub client: reqwest::Client,
-    pub token: String,
+    pub credentials: Credentials,
+    /// Base url of the GitHub API, i.e. `https://github.mycorp.com/api/v3` for GitHub Enterprise Server
+    pub base_url: String,
+    /// Maximum number of times a rate limited or transiently failing request is retried
+    pub max_retries: usize,
+    /// Number of results requested per page, at most 100
+    pub per_page: usize,
+    /// Cache of list pages, which are then requested conditionally
+    pub cache: Option<Cache>,
+    /// User-Agent sent with each request
+    pub user_agent: String,
+    /// Version of the REST API sent with each request as `X-GitHub-Api-Version`
+    pub api_version: String,
+    /// Limit on how often requests are sent, shared by every clone of this client
+    pub throttle: Option<Throttle>,
+    /// Print requests which would change anything in place of sending them
+    pub dry_run: bool,
 }
 
 enum PageState {
     Fetch(Box<RequestBuilder>),
+    /// A page linked to by the previous page, retried after recoverable failures
+    Next(String),
     End,
 }
 
+/// Which failures a request may be retried on
+#[derive(Clone, Copy, PartialEq)]
+enum Retry {
+    /// Rate limits, transient server errors and connection failures.
+    /// Only suitable for requests that are safe to repeat
+    Always,
+    /// Only failures of requests GitHub did not apply, which are rate limits and failures to
+    /// connect, for requests that must not be applied twice
+    Unapplied,
+}
+
 impl Requests {
+    /// Resolves an api path relative to this client's base url
+    fn url(
+        &self,
+        path: &str,
+    ) -> String {
+        format!(
+            "{base}{path}",
+            base = self.base_url.trim_end_matches('/'),
+            path = path
+        )
+    }
+
     fn builder(
         &self,
         builder: RequestBuilder,
     ) -> RequestBuilder {
-        builder.header("User-Agent", env!("CARGO_PKG_NAME")).header(
-            "Authorization",
-            format!("bearer {token}", token = self.token),
-        )
+        builder
+            .header("User-Agent", self.user_agent.as_str())
+            .header("X-GitHub-Api-Version", self.api_version.as_str())
+    }
+
+    /// Sends a single request, logging its method and url along with the response's
+    /// status and pagination links. Headers are never logged so that credentials are not leaked
+    async fn execute(
+        &self,
+        builder: RequestBuilder,
+    ) -> Result<Response, reqwest::Error> {
+        let request = builder.build()?;
+        let (method, url) = (request.method().clone(), request.url().clone());
+        if let Some(throttle) = &self.throttle {
+            throttle.wait(jitter()).await;
+        }
+        debug!("{} {}", method, url);
+        let response = self.client.execute(request).await;
+        match &response {
+            Ok(response) => match response.headers().get(LINK) {
+                Some(link) => debug!(
+                    "{} {} {} Link: {}",
+                    method,
+                    url,
+                    response.status(),
+                    link.to_str().unwrap_or_default()
+                ),
+                None => debug!("{} {} {}", method, url, response.status()),
+            },
+            Err(err) => debug!("{} {} failed: {}", method, url, err),
+        }
+        response
+    }
+
+    /// Authorization header value for the current credentials, minting a GitHub App
+    /// installation token when none is cached or the cached one is about to expire
+    async fn authorization(&self) -> Result<String, ActionsError> {
+        let (app, installation_token) = match &self.credentials {
+            Credentials::Token(token) => return Ok(format!("bearer {token}", token = token)),
+            Credentials::App {
+                app,
+                installation_token,
+            } => (app, installation_token),
+        };
+        let mut cached = installation_token.lock().await;
+        if let Some(token) = cached.as_ref().filter(|token| token.valid_at(Utc::now())) {
+            return Ok(format!("bearer {token}", token = token.token));
+        }
+        let jwt = app
+            .jwt(Utc::now())
+            .map_err(|err| ActionsError::Auth(format!("app JWT could not be created: {}", err)))?;
+        let response = self
+            .execute(
+                self.builder(self.client.post(&self.url(&format!(
+                    "/app/installations/{installation_id}/access_tokens",
+                    installation_id = app.installation_id
+                ))))
+                .header("Authorization", format!("Bearer {jwt}", jwt = jwt)),
+            )
+            .await
+            .map_err(ActionsError::Http)?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        let token = response
+            .json::<InstallationToken>()
+            .await
+            .map_err(ActionsError::Http)?;
+        let authorization = format!("bearer {token}", token = token.token);
+        *cached = Some(token);
+        Ok(authorization)
     }
 
     fn get(
@@ -194,6 +719,13 @@ impl Requests {
         self.builder(self.client.get(url))
     }
 
+    fn post(
+        &self,
+        url: &str,
+    ) -> RequestBuilder {
+        self.builder(self.client.post(url))
+    }
+
     fn put(
         &self,
         url: &str,
@@ -209,13 +741,163 @@ impl Requests {
         self.builder(self.client.delete(url))
     }
 
-    /// Drives a paginated pull-oriented stream of api results to completion
+    /// Sends a request, retrying failures permitted by `retry` up to `max_retries` times.
+    /// Rate limited requests wait until the rate limit resets, other failures back off exponentially
+    async fn send(
+        &self,
+        builder: RequestBuilder,
+        retry: Retry,
+    ) -> Result<Response, ActionsError> {
+        if self.dry_run {
+            if let Some(request) = builder
+                .try_clone()
+                .and_then(|builder| builder.build().ok())
+                .filter(|request| !request.method().is_safe())
+            {
+                println!("Would {} {}", request.method(), request.url());
+                return Ok(http::Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .body(Vec::new())
+                    .map_err(|err| ActionsError::Decode(err.into()))?
+                    .into());
+            }
+        }
+        let builder = builder.header("Authorization", self.authorization().await?);
+        let mut retries = 0;
+        let mut rate_limited_for = Duration::default();
+        loop {
+            let attempt = match builder.try_clone() {
+                Some(attempt) => attempt,
+                // requests with streaming bodies can only be sent once
+                None => return self.execute(builder).await.map_err(ActionsError::Http),
+            };
+            let exhausted = retries >= self.max_retries;
+            let delay = match self.execute(attempt).await {
+                Ok(response) if exhausted => return Ok(response),
+                Ok(response) => {
+                    if let Some(delay) = rate_limit_delay(&response, Utc::now())
+                        .filter(|delay| rate_limited_for + *delay <= MAX_RATE_LIMIT_WAIT)
+                    {
+                        eprintln!("Rate limited. Retrying in {}", format_duration(delay));
+                        rate_limited_for += delay;
+                        delay
+                    } else if retry == Retry::Always && is_transient(response.status()) {
+                        let delay = backoff(retries, jitter());
+                        eprintln!(
+                            "Request failed with status {}. Retrying in {}",
+                            response.status(),
+                            format_duration(delay)
+                        );
+                        delay
+                    } else {
+                        return Ok(response);
+                    }
+                }
+                Err(err) if !exhausted && is_retryable(&err, retry) => {
+                    let delay = backoff(retries, jitter());
+                    eprintln!("{}. Retrying in {}", err, format_duration(delay));
+                    delay
+                }
+                Err(err) => return Err(ActionsError::Http(err)),
+            };
+            retries += 1;
+            delay_for(delay).await;
+        }
+    }
+
+    /// Fetches a single page of results, retrying failures up to `max_retries` times.
+    /// Returns the deserialized page along with a link to the next page, if any
+    async fn page<P: DeserializeOwned>(
+        &self,
+        builder: RequestBuilder,
+    ) -> Result<(P, Option<String>), ActionsError> {
+        let url = match &self.cache {
+            Some(_) => builder
+                .try_clone()
+                .and_then(|builder| builder.build().ok())
+                .map(|request| request.url().to_string()),
+            None => None,
+        };
+        // a cached page is only revalidated when it can still be read
+        let cached = match (&self.cache, &url) {
+            (Some(cache), Some(url)) => cache.get(url).and_then(|entry| {
+                let page = serde_json::from_str::<P>(&entry.body).ok()?;
+                Some((entry, page))
+            }),
+            _ => None,
+        };
+        let builder = match &cached {
+            Some((entry, _)) => builder.header(IF_NONE_MATCH, entry.etag.as_str()),
+            None => builder,
+        };
+        let response = self.send(builder, Retry::Always).await?;
+        if let (StatusCode::NOT_MODIFIED, Some((entry, page))) = (response.status(), cached) {
+            debug!("{} is not modified", response.url());
+            return Ok((page, entry.next));
+        }
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        let next = next_link(&response);
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(String::from);
+        match (&self.cache, url, etag) {
+            (Some(cache), Some(url), Some(etag)) => {
+                let body = response.text().await.map_err(ActionsError::Http)?;
+                let page =
+                    serde_json::from_str(&body).map_err(|err| ActionsError::Decode(err.into()))?;
+                cache.put(
+                    &url,
+                    &Entry {
+                        etag,
+                        next: next.clone(),
+                        body,
+                    },
+                );
+                Ok((page, next))
+            }
+            _ => Ok((
+                response.json::<P>().await.map_err(ActionsError::Http)?,
+                next,
+            )),
+        }
+    }
+
+    /// Fetches a page linked to by the previous page, fetching it again up to
+    /// `NEXT_PAGE_RETRIES` times when it fails recoverably, i.e. with a server error
+    async fn next_page<P: DeserializeOwned>(
+        &self,
+        link: &str,
+    ) -> Result<(P, Option<String>), ActionsError> {
+        let mut retries = 0;
+        loop {
+            match self.page::<P>(self.get(link)).await {
+                Err(err) if retries < NEXT_PAGE_RETRIES && err.recoverable() => {
+                    let delay = backoff(retries, jitter());
+                    eprintln!(
+                        "Fetching the next page failed: {}. Retrying in {}",
+                        err,
+                        format_duration(delay)
+                    );
+                    retries += 1;
+                    delay_for(delay).await;
+                }
+                page => return page,
+            }
+        }
+    }
+
+    /// Drives a paginated pull-oriented stream of api results to completion.
+    /// A failure to fetch any page, once any retries are exhausted, is yielded as the stream's final item
     fn paginate<F, C, P: DeserializeOwned, I: DeserializeOwned>(
         self,
         state: PageState,
         mut into: F,
         mut cont: C,
-    ) -> impl Stream<Item = I>
+    ) -> impl Stream<Item = Result<I, ActionsError>>
     where
         F: FnMut(P) -> Vec<I> + Copy,
         C: FnMut(&Vec<I>) -> bool + Copy,
@@ -223,71 +905,126 @@ impl Requests {
         stream::unfold(state, move |state| {
             let this = self.clone();
             async move {
-                match state {
-                    PageState::Fetch(builder) => {
-                        let clone = builder.try_clone();
-                        let response = builder.send().await.ok()?;
-                        let next = next_link(&response);
-                        if !response.status().is_success() {
-                            if let Some(clone) = clone {
-                                println!(
-                                    "request {:#?} was unsuccessful {:#?}",
-                                    clone.build().ok()?.url(),
-                                    response.status()
-                                );
-                                println!("{}", response.text().await.ok()?);
-                                return None;
-                            }
-                        }
-                        let items = into(response.json::<P>().await.ok()?);
+                let page = match state {
+                    PageState::Fetch(builder) => this.page::<P>(*builder).await,
+                    PageState::Next(link) => this.next_page::<P>(&link).await,
+                    PageState::End => return None,
+                };
+                match page {
+                    Ok((page, next)) => {
+                        let items = into(page);
                         let next_state = match next {
-                            Some(link) if cont(&items) => {
-                                PageState::Fetch(Box::new(this.get(&link)))
-                            }
+                            Some(link) if cont(&items) => PageState::Next(link),
                             _ => PageState::End,
                         };
-                        Some((stream::iter(items), next_state))
+                        Some((
+                            stream::iter(items.into_iter().map(Ok).collect::<Vec<_>>()),
+                            next_state,
+                        ))
                     }
-                    PageState::End => return None,
+                    Err(err) => Some((stream::iter(vec![Err(err)]), PageState::End)),
                 }
             }
         })
         .flatten()
     }
 
+    /// Sends a request to any api path, returning its response body. Fields are sent as the
+    /// query parameters of GET requests and as the json object body of other requests
+    pub async fn api(
+        &self,
+        method: Method,
+        path: &str,
+        fields: &[(String, Value)],
+    ) -> Result<String, ActionsError> {
+        let url = self.url(&format!("/{}", path.trim_start_matches('/')));
+        let (builder, retry) = if method == Method::GET {
+            (self.get(&url).query(&query_params(fields)), Retry::Always)
+        } else {
+            let builder = self.builder(self.client.request(method, &url));
+            let builder = if fields.is_empty() {
+                builder
+            } else {
+                builder.json(&fields.iter().cloned().collect::<Map<_, _>>())
+            };
+            (builder, Retry::Unapplied)
+        };
+        let response = self.send(builder, retry).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        response.text().await.map_err(ActionsError::Http)
+    }
+
+    /// Gets every page of json results of any api path by following its `next` links
+    pub fn api_pages(
+        self,
+        path: &str,
+        fields: &[(String, Value)],
+    ) -> impl Stream<Item = Result<Value, ActionsError>> {
+        let builder = self
+            .get(&self.url(&format!("/{}", path.trim_start_matches('/'))))
+            .query(&query_params(fields));
+        self.paginate(
+            PageState::Fetch(Box::new(builder)),
+            |page: Value| vec![page],
+            |_| true,
+        )
+    }
+
+    /// Discovers repositories with workflows in an organization using code search.
+    /// Discovery stops once `limit` repositories are found, which may leave the workflows
+    /// of the last repositories found incomplete. Also returns whether code search left out
+    /// some matches, in which case some repositories may be missing. `progress` reports how
+    /// many repositories were found so far
     pub async fn repos(
         self,
         org: String,
-    ) -> Vec<Repo> {
-        let builder = self.get("https://api.github.com/search/code").query(&[
-            ("per_page", "100"),
+        limit: usize,
+        progress: &ProgressBar,
+    ) -> Result<(Vec<Repo>, bool), ActionsError> {
+        let incomplete = AtomicBool::new(false);
+        let builder = self.get(&self.url("/search/code")).query(&[
+            ("per_page", self.per_page.to_string().as_str()),
             (
                 "q",
                 format!("org:{org} path:.github/workflows", org = org).as_str(),
             ),
         ]);
-        self.paginate(
-            PageState::Fetch(Box::new(builder)),
-            |s: CodeSearch| s.items,
-            |_| true,
-        )
-        .fold(
-            BTreeMap::default(),
-            move |mut state: BTreeMap<String, Vec<String>>, item| async {
-                state
-                    .entry(item.repository.full_name)
-                    .or_insert_with(Vec::new)
-                    .push(item.path);
-                state
-            },
-        )
-        .await
-        .into_iter()
-        .map(|(full_name, workflows)| Repo {
-            full_name,
-            workflows,
-        })
-        .collect()
+        let mut hits = self
+            .paginate(
+                PageState::Fetch(Box::new(builder)),
+                |s: CodeSearch| {
+                    if s.incomplete() {
+                        incomplete.store(true, Ordering::Relaxed);
+                    }
+                    s.items
+                },
+                |_| true,
+            )
+            .boxed();
+        // code search orders hits by relevance, so paths are collected into a set to list them consistently
+        let mut repos = BTreeMap::<String, BTreeSet<String>>::new();
+        while let Some(item) = hits.try_next().await? {
+            if repos.len() >= limit && !repos.contains_key(&item.repository.full_name) {
+                break;
+            }
+            repos
+                .entry(item.repository.full_name)
+                .or_default()
+                .insert(item.path);
+            progress.set_message(&format!("Found {} repos", repos.len()));
+        }
+        let repos = repos
+            .into_iter()
+            .map(|(full_name, workflows)| Repo {
+                full_name,
+                workflows: workflows.into_iter().collect(),
+                archived: None,
+                fork: None,
+            })
+            .collect();
+        Ok((repos, incomplete.load(Ordering::Relaxed)))
     }
 
     /// Gets your public key, which you must store. You need your public key to use other secrets endpoints.
@@ -297,70 +1034,72 @@ impl Requests {
     /// See the [developer docs](https://developer.github.com/v3/actions/secrets/#get-your-public-key) for more information
     pub async fn public_key(
         &self,
-        repository: impl AsRef<str>,
-    ) -> Result<Key, Box<dyn Error>> {
-        Ok(self
-            .get(&format!(
-                "https://api.github.com/repos/{repo}/actions/secrets/public-key",
-                repo = repository.as_ref()
-            ))
-            .send()
-            .await?
-            .json::<Key>()
-            .await?)
+        scope: &SecretScope,
+    ) -> Result<Key, ActionsError> {
+        let builder = self.get(&self.url(&format!("{secrets}/public-key", secrets = scope.path())));
+        let response = self.send(builder, Retry::Always).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(response.json::<Key>().await?)
     }
 
+    /// Creates or updates a secret with a value encrypted using the owner's public key.
+    /// Returns true when the secret was created rather than updated
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/actions/secrets#create-or-update-a-repository-secret) for more information
     pub async fn upsert_secret(
         self,
-        repository: String,
+        scope: SecretScope,
         name: String,
-        encrypted_value: String,
-        key_id: String,
-    ) -> Result<(), Box<dyn Error>> {
-        self.put(&format!(
-            "https://api.github.com/repos/{repo}/actions/secrets/{name}",
-            repo = repository,
-            name = name
-        ))
-        .json(&SecretValue {
-            encrypted_value,
-            key_id,
-        })
-        .send()
-        .await?;
-        Ok(())
+        value: SecretValue,
+    ) -> Result<bool, ActionsError> {
+        if self.dry_run {
+            println!("Would encrypt and upsert a value for secret {}", name);
+        }
+        let builder = self
+            .put(&self.url(&format!(
+                "{secrets}/{name}",
+                secrets = scope.path(),
+                name = name
+            )))
+            .json(&value);
+        let response = self.send(builder, Retry::Always).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(response.status() == StatusCode::CREATED)
     }
 
     pub async fn delete_secret(
         self,
-        repository: String,
+        scope: SecretScope,
         name: String,
-    ) -> Result<(), Box<dyn Error>> {
-        self.delete(&format!(
-            "https://api.github.com/repos/{repo}/actions/secrets/{name}",
-            repo = repository,
+    ) -> Result<(), ActionsError> {
+        let builder = self.delete(&self.url(&format!(
+            "{secrets}/{name}",
+            secrets = scope.path(),
             name = name
-        ))
-        .send()
-        .await?;
+        )));
+        let response = self.send(builder, Retry::Unapplied).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
         Ok(())
     }
 
-    /// Lists all secrets available in a repository without revealing their encrypted values.
+    /// Lists all secrets available in a repository or organization without revealing their encrypted values.
     /// Anyone with write access to the repository can use this endpoint.
     /// GitHub Apps must have the secrets permission to use this endpoint.
     ///
     /// See the [developer docs](https://developer.github.com/v3/actions/secrets/#list-secrets-for-a-repository) for more information
     pub fn secrets(
         self,
-        repository: String,
-    ) -> impl Stream<Item = Secret> {
+        scope: SecretScope,
+    ) -> impl Stream<Item = Result<Secret, ActionsError>> {
         let builder = self
-            .get(&format!(
-                "https://api.github.com/repos/{repo}/actions/secrets",
-                repo = repository
-            ))
-            .query(&[("per_page", "100")]);
+            .get(&self.url(&scope.path()))
+            .query(&[("per_page", self.per_page)]);
         self.paginate(
             PageState::Fetch(Box::new(builder)),
             |w: Secrets| w.secrets,
@@ -375,14 +1114,14 @@ impl Requests {
         self,
         repository: String,
         run_id: usize,
-    ) -> impl Stream<Item = Artifact> {
+    ) -> impl Stream<Item = Result<Artifact, ActionsError>> {
         let builder = self
-            .get(&format!(
-                "https://api.github.com/repos/{repo}/actions/runs/{run_id}/artifacts",
+            .get(&self.url(&format!(
+                "/repos/{repo}/actions/runs/{run_id}/artifacts",
                 repo = repository,
                 run_id = run_id
-            ))
-            .query(&[("per_page", "100")]);
+            )))
+            .query(&[("per_page", self.per_page)]);
         self.paginate(
             PageState::Fetch(Box::new(builder)),
             |w: Artifacts| w.artifacts,
@@ -390,6 +1129,63 @@ impl Requests {
         )
     }
 
+    /// Lists a repository's actions caches, most recently used first. Anyone with read access to the repository can use this endpoint.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/actions/cache#list-github-actions-caches-for-a-repository) for more information
+    pub fn caches(
+        self,
+        repository: String,
+    ) -> impl Stream<Item = Result<ActionsCache, ActionsError>> {
+        let builder = self
+            .get(&self.url(&format!("/repos/{repo}/actions/caches", repo = repository)))
+            .query(&[("per_page", self.per_page)]);
+        self.paginate(
+            PageState::Fetch(Box::new(builder)),
+            |c: ActionsCaches| c.actions_caches,
+            |_| true,
+        )
+    }
+
+    /// Deletes an actions cache by its id. Anyone with write access to the repository can use this endpoint.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/actions/cache#delete-a-github-actions-cache-for-a-repository-using-a-cache-id) for more information
+    pub async fn delete_cache(
+        self,
+        repository: String,
+        cache_id: usize,
+    ) -> Result<(), ActionsError> {
+        let builder = self.delete(&self.url(&format!(
+            "/repos/{repo}/actions/caches/{cache_id}",
+            repo = repository,
+            cache_id = cache_id
+        )));
+        let response = self.send(builder, Retry::Unapplied).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(())
+    }
+
+    /// Deletes a workflow run. Anyone with write access to the repository can use this endpoint.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/actions/workflow-runs#delete-a-workflow-run) for more information
+    pub async fn delete_run(
+        self,
+        repository: String,
+        run_id: usize,
+    ) -> Result<(), ActionsError> {
+        let builder = self.delete(&self.url(&format!(
+            "/repos/{repo}/actions/runs/{run_id}",
+            repo = repository,
+            run_id = run_id
+        )));
+        let response = self.send(builder, Retry::Unapplied).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(())
+    }
+
     /// Deletes an artifact for a workflow run. Anyone with write access to the repository can use this endpoint. GitHub Apps must have the actions permission to use this endpoint.
     ///
     /// See the [developer docs](https://developer.github.com/v3/actions/artifacts/#delete-an-artifact) for more information
@@ -397,78 +1193,605 @@ impl Requests {
         self,
         repository: String,
         artifact_id: usize,
-    ) -> Result<(), Box<dyn Error>> {
-        self.delete(&format!(
-            "https://api.github.com/repos/{repo}/actions/artifacts/{artifact_id}",
+    ) -> Result<(), ActionsError> {
+        let builder = self.delete(&self.url(&format!(
+            "/repos/{repo}/actions/artifacts/{artifact_id}",
             repo = repository,
             artifact_id = artifact_id
-        ))
-        .send()
-        .await?;
+        )));
+        let response = self.send(builder, Retry::Unapplied).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
         Ok(())
     }
 
-    /// Gets the number of billable minutes used by a specific workflow during the current billing cycle. Billable minutes only apply to workflows in private repositories that use GitHub-hosted runners. Usage is listed for each GitHub-hosted runner operating system in milliseconds. Any job re-runs are also included in the usage.
-    pub async fn workflow_usage(
+    /// Downloads an artifact's zip archive. The api responds with a redirect to a short lived
+    /// signed url which is followed. The returned response's body is left unread so that callers may stream it.
+    ///
+    /// See the [developer docs](https://developer.github.com/v3/actions/artifacts/#download-an-artifact) for more information
+    pub async fn download_artifact(
         &self,
-        repository: String,
-        workflow: usize,
-    ) -> Result<Usage, Box<dyn Error>> {
-        Ok(self
-            .get(&format!(
-                "https://api.github.com/repos/{repo}/actions/workflows/{workflow}/timing",
-                repo = repository,
-                workflow = workflow
-            ))
-            .send()
-            .await?
-            .json()
-            .await?)
+        artifact: &Artifact,
+    ) -> Result<Response, ActionsError> {
+        let builder = self
+            .get(&artifact.archive_download_url)
+            .timeout(DOWNLOAD_TIMEOUT);
+        let response = self.send(builder, Retry::Always).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(response)
     }
 
-    /// Lists the workflows in a repository. Anyone with read access to the repository can use this endpoint.
-    /// GitHub Apps must have the actions permission to use this endpoint.
+    /// Lists the jobs of a workflow run given its `jobs_url`.
     ///
-    /// See the [developer docs](https://developer.github.com/v3/actions/workflows/#list-repository-workflows) for more information
-    pub fn workflows(
+    /// See the [developer docs](https://docs.github.com/en/rest/actions/workflow-jobs#list-jobs-for-a-workflow-run) for more information
+    pub fn jobs(
         self,
-        repository: String,
-    ) -> impl Stream<Item = Workflow> {
-        let builder = self
-            .get(&format!(
-                "https://api.github.com/repos/{repo}/actions/workflows",
-                repo = repository
-            ))
-            .query(&[("per_page", "100")]);
+        jobs_url: &str,
+    ) -> impl Stream<Item = Result<Job, ActionsError>> {
+        let builder = self.get(jobs_url).query(&[("per_page", self.per_page)]);
         self.paginate(
             PageState::Fetch(Box::new(builder)),
-            |w: Workflows| w.workflows,
+            |j: Jobs| j.jobs,
             |_| true,
         )
     }
 
-    /// List all workflow runs for a workflow.
+    /// Gets a specific workflow run. Anyone with read access to the repository can use this endpoint.
+    /// A run created moments ago may not be found until it has replicated, so a run which
+    /// isn't found is fetched again, backing off, up to `max_retries` times. Only this single
+    /// run request retries on `404 Not Found`
     ///
-    /// https://developer.github.com/v3/actions/workflow_runs/#list-workflow-runs
-    pub fn runs(
-        self,
-        repository: String,
-        workflow: String,
-        since: DateTime<Utc>,
-    ) -> impl Stream<Item = Run> {
-        let builder = self
-            .get(&format!(
-                "https://api.github.com/repos/{repo}/actions/workflows/{workflow}/runs",
-                repo = repository,
-                workflow = urlencode(workflow.as_bytes()).collect::<String>()
-            ))
-            .query(&[("per_page", "100"), ("status", "completed")]);
-        self.paginate(
-            PageState::Fetch(Box::new(builder)),
-            |w: Runs| w.workflow_runs,
-            move |runs: &Vec<Run>| runs.iter().any(|run| run.created_at >= since),
-        )
+    /// See the [developer docs](https://developer.github.com/v3/actions/workflow_runs/#get-a-workflow-run) for more information
+    pub async fn run(
+        &self,
+        repository: impl AsRef<str>,
+        run_id: usize,
+    ) -> Result<Run, ActionsError> {
+        let url = self.url(&format!(
+            "/repos/{repo}/actions/runs/{run_id}",
+            repo = repository.as_ref(),
+            run_id = run_id
+        ));
+        let mut retries = 0;
+        loop {
+            let response = self.send(self.get(&url), Retry::Always).await?;
+            if response.status() == StatusCode::NOT_FOUND && retries < self.max_retries {
+                let delay = backoff(retries, jitter());
+                eprintln!(
+                    "Run {} was not found yet. Retrying in {}",
+                    run_id,
+                    format_duration(delay)
+                );
+                retries += 1;
+                delay_for(delay).await;
+                continue;
+            }
+            if !response.status().is_success() {
+                return Err(ActionsError::from_response(response).await);
+            }
+            return Ok(response.json().await?);
+        }
     }
+
+    /// Downloads a workflow run's logs as a zip archive containing a log file per job step.
+    /// Logs are only retained for a limited time, after which the api responds with `410 Gone`
+    ///
+    /// See the [developer docs](https://developer.github.com/v3/actions/workflow_runs/#list-workflow-run-logs) for more information
+    pub async fn run_logs(
+        &self,
+        repository: impl AsRef<str>,
+        run_id: usize,
+    ) -> Result<Vec<u8>, ActionsError> {
+        let builder = self
+            .get(&self.url(&format!(
+                "/repos/{repo}/actions/runs/{run_id}/logs",
+                repo = repository.as_ref(),
+                run_id = run_id
+            )))
+            .timeout(DOWNLOAD_TIMEOUT);
+        let response = self.send(builder, Retry::Always).await?;
+        match response.status() {
+            StatusCode::GONE => Err(ActionsError::Api {
+                status: StatusCode::GONE,
+                message: format!(
+                    "logs for run {} have expired and are no longer available",
+                    run_id
+                ),
+            }),
+            status if !status.is_success() => Err(ActionsError::from_response(response).await),
+            _ => Ok(response.bytes().await?.to_vec()),
+        }
+    }
+
+    /// Downloads the plain text log of a single job. Like run logs, job logs expire and
+    /// the api then responds with `410 Gone`
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/actions/workflow-jobs#download-job-logs-for-a-workflow-run) for more information
+    pub async fn job_logs(
+        &self,
+        repository: impl AsRef<str>,
+        job_id: usize,
+    ) -> Result<Vec<u8>, ActionsError> {
+        let builder = self
+            .get(&self.url(&format!(
+                "/repos/{repo}/actions/jobs/{job_id}/logs",
+                repo = repository.as_ref(),
+                job_id = job_id
+            )))
+            .timeout(DOWNLOAD_TIMEOUT);
+        let response = self.send(builder, Retry::Always).await?;
+        match response.status() {
+            StatusCode::GONE => Err(ActionsError::Api {
+                status: StatusCode::GONE,
+                message: format!(
+                    "logs for job {} have expired and are no longer available",
+                    job_id
+                ),
+            }),
+            status if !status.is_success() => Err(ActionsError::from_response(response).await),
+            _ => Ok(response.bytes().await?.to_vec()),
+        }
+    }
+
+    /// Cancels a workflow run using its `cancel_url`
+    ///
+    /// See the [developer docs](https://developer.github.com/v3/actions/workflow_runs/#cancel-a-workflow-run) for more information
+    pub async fn cancel(
+        &self,
+        run: &Run,
+    ) -> Result<(), ActionsError> {
+        let response = self
+            .send(self.post(&run.cancel_url), Retry::Unapplied)
+            .await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(())
+    }
+
+    /// Re-runs a workflow run, or only its failed jobs when `failed_only` is set.
+    /// Returns the run's html url when the API provides one.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/actions/workflow-runs#re-run-a-workflow) for more information
+    pub async fn rerun(
+        self,
+        repository: String,
+        run_id: usize,
+        failed_only: bool,
+    ) -> Result<Option<String>, ActionsError> {
+        let builder = self.post(&self.url(&format!(
+            "/repos/{repo}/actions/runs/{run_id}/{action}",
+            repo = repository,
+            run_id = run_id,
+            action = if failed_only {
+                "rerun-failed-jobs"
+            } else {
+                "rerun"
+            }
+        )));
+        let response = self.send(builder, Retry::Unapplied).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(response
+            .json::<Rerun>()
+            .await
+            .ok()
+            .and_then(|rerun| rerun.html_url))
+    }
+
+    /// Gets the billable time of a single workflow run, by runner os.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/actions/workflow-runs#get-workflow-run-usage) for more information
+    pub async fn run_usage(
+        &self,
+        repository: impl AsRef<str>,
+        run_id: usize,
+    ) -> Result<WorkflowUsage, ActionsError> {
+        let builder = self.get(&self.url(&format!(
+            "/repos/{repo}/actions/runs/{run_id}/timing",
+            repo = repository.as_ref(),
+            run_id = run_id
+        )));
+        let response = self.send(builder, Retry::Always).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Gets the GitHub Actions minutes an organization or user has used and has included in
+    /// their plan. Requires a token of an organization or user admin.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/billing/billing#get-github-actions-billing-for-an-organization) for more information
+    pub async fn actions_billing(
+        &self,
+        owner: &str,
+    ) -> Result<ActionsBilling, ActionsError> {
+        let mut failure = None;
+        for account in &["orgs", "users"] {
+            let builder = self.get(&self.url(&format!(
+                "/{account}/{owner}/settings/billing/actions",
+                account = account,
+                owner = owner
+            )));
+            let response = self.send(builder, Retry::Always).await?;
+            if response.status().is_success() {
+                return Ok(response.json().await?);
+            }
+            // the owner is only known to be an organization or a user once one isn't missing
+            let err = ActionsError::from_response(response).await;
+            if failure.is_none() || !matches!(err, ActionsError::NotFound(_)) {
+                failure = Some(err);
+            }
+        }
+        Err(failure.expect("billing is requested of at least one account"))
+    }
+
+    /// Gets the number of billable minutes used by a specific workflow during the current billing cycle. Billable minutes only apply to workflows in private repositories that use GitHub-hosted runners. Usage is listed for each GitHub-hosted runner operating system in milliseconds. Any job re-runs are also included in the usage.
+    pub async fn workflow_usage(
+        &self,
+        repository: String,
+        workflow: usize,
+    ) -> Result<WorkflowUsage, ActionsError> {
+        let builder = self.get(&self.url(&format!(
+            "/repos/{repo}/actions/workflows/{workflow}/timing",
+            repo = repository,
+            workflow = workflow
+        )));
+        let response = self.send(builder, Retry::Always).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Lists the workflows in a repository. Anyone with read access to the repository can use this endpoint.
+    /// GitHub Apps must have the actions permission to use this endpoint.
+    ///
+    /// See the [developer docs](https://developer.github.com/v3/actions/workflows/#list-repository-workflows) for more information
+    pub fn workflows(
+        self,
+        repository: String,
+    ) -> impl Stream<Item = Result<Workflow, ActionsError>> {
+        let builder = self
+            .get(&self.url(&format!(
+                "/repos/{repo}/actions/workflows",
+                repo = repository
+            )))
+            .query(&[("per_page", self.per_page)]);
+        self.paginate(
+            PageState::Fetch(Box::new(builder)),
+            |w: Workflows| w.workflows,
+            |_| true,
+        )
+    }
+
+    /// Gets the user the token authenticates as, along with the OAuth scopes granted to
+    /// classic tokens. Fine-grained and app tokens have no scopes.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/users/users#get-the-authenticated-user) for more information
+    pub async fn user(&self) -> Result<(User, Option<Vec<String>>), ActionsError> {
+        let builder = self.get(&self.url("/user"));
+        let response = self.send(builder, Retry::Always).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        let scopes = response
+            .headers()
+            .get("x-oauth-scopes")
+            .and_then(|value| value.to_str().ok())
+            .map(|scopes| {
+                scopes
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|scope| !scope.is_empty())
+                    .map(String::from)
+                    .collect()
+            });
+        Ok((response.json::<User>().await?, scopes))
+    }
+
+    /// Gets a repository's details, i.e. whether it is archived or a fork.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/repos/repos#get-a-repository) for more information
+    pub async fn repo(
+        &self,
+        repository: impl AsRef<str>,
+    ) -> Result<RepoDetails, ActionsError> {
+        let builder = self.get(&self.url(&format!("/repos/{repo}", repo = repository.as_ref())));
+        let response = self.send(builder, Retry::Always).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(response.json::<RepoDetails>().await?)
+    }
+
+    /// Gets the numeric id of a repository, which environment apis are addressed by
+    pub async fn repo_id(
+        &self,
+        repository: impl AsRef<str>,
+    ) -> Result<u64, ActionsError> {
+        Ok(self.repo(repository).await?.id)
+    }
+
+    /// Gets a specific workflow. Anyone with read access to the repository can use this endpoint.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/actions/workflows#get-a-workflow) for more information
+    pub async fn workflow(
+        &self,
+        repository: impl AsRef<str>,
+        workflow: usize,
+    ) -> Result<Workflow, ActionsError> {
+        let builder = self.get(&self.url(&format!(
+            "/repos/{repo}/actions/workflows/{workflow}",
+            repo = repository.as_ref(),
+            workflow = workflow
+        )));
+        let response = self.send(builder, Retry::Always).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Gets the contents of a file at a branch or tag, or on the repository's default branch.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/repos/contents#get-repository-content) for more information
+    pub async fn file_contents(
+        &self,
+        repository: impl AsRef<str>,
+        path: &str,
+        git_ref: Option<&str>,
+    ) -> Result<String, ActionsError> {
+        let mut builder = self.get(&self.url(&format!(
+            "/repos/{repo}/contents/{path}",
+            repo = repository.as_ref(),
+            path = path
+        )));
+        if let Some(git_ref) = git_ref {
+            builder = builder.query(&[("ref", git_ref)]);
+        }
+        let response = self.send(builder, Retry::Always).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        let Contents { content } = response.json().await?;
+        decode_contents(&content)
+    }
+
+    /// Enables or disables a workflow, setting its state to `active` or `disabled_manually`.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/actions/workflows#enable-a-workflow) for more information
+    pub async fn set_workflow_enabled(
+        &self,
+        repository: impl AsRef<str>,
+        workflow: usize,
+        enabled: bool,
+    ) -> Result<(), ActionsError> {
+        let action = if enabled { "enable" } else { "disable" };
+        let builder = self.put(&self.url(&format!(
+            "/repos/{repo}/actions/workflows/{workflow}/{action}",
+            repo = repository.as_ref(),
+            workflow = workflow,
+            action = action
+        )));
+        let response = self.send(builder, Retry::Always).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(())
+    }
+
+    /// Triggers a workflow_dispatch event for a workflow configured to run on `workflow_dispatch`.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/actions/workflows#create-a-workflow-dispatch-event) for more information
+    pub async fn dispatch(
+        &self,
+        repository: impl AsRef<str>,
+        workflow: usize,
+        dispatch: &Dispatch,
+    ) -> Result<(), ActionsError> {
+        let builder = self
+            .post(&self.url(&format!(
+                "/repos/{repo}/actions/workflows/{workflow}/dispatches",
+                repo = repository.as_ref(),
+                workflow = workflow
+            )))
+            .json(dispatch);
+        let response = self.send(builder, Retry::Unapplied).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(())
+    }
+
+    /// Creates a repository_dispatch event, triggering workflows that run on its event type.
+    ///
+    /// See the [developer docs](https://docs.github.com/en/rest/repos/repos#create-a-repository-dispatch-event) for more information
+    pub async fn repository_dispatch(
+        &self,
+        repository: impl AsRef<str>,
+        dispatch: &RepositoryDispatch,
+    ) -> Result<(), ActionsError> {
+        let builder = self
+            .post(&self.url(&format!(
+                "/repos/{repo}/dispatches",
+                repo = repository.as_ref()
+            )))
+            .json(dispatch);
+        let response = self.send(builder, Retry::Unapplied).await?;
+        if !response.status().is_success() {
+            return Err(ActionsError::from_response(response).await);
+        }
+        Ok(())
+    }
+
+    /// List workflow runs for a workflow matching a filter. Status and event are filtered by the api.
+    /// Branches and actors are matched ignoring case, which the api does not support, so they are filtered as runs arrive.
+    /// Runs are listed newest first, so paging stops once a page holds only runs created before `since`.
+    ///
+    /// https://developer.github.com/v3/actions/workflow_runs/#list-workflow-runs
+    pub fn runs(
+        self,
+        repository: String,
+        workflow: String,
+        filter: RunFilter,
+    ) -> impl Stream<Item = Result<Run, ActionsError>> {
+        let url = self.url(&format!(
+            "/repos/{repo}/actions/workflows/{workflow}/runs",
+            repo = repository,
+            workflow = urlencode(workflow.as_bytes()).collect::<String>()
+        ));
+        self.filtered_runs(&url, filter)
+    }
+
+    /// List workflow runs of every workflow in a repository matching a filter, filtered and paged
+    /// as [`Requests::runs`] does for a single workflow.
+    ///
+    /// https://docs.github.com/en/rest/actions/workflow-runs#list-workflow-runs-for-a-repository
+    pub fn all_runs(
+        self,
+        repository: String,
+        filter: RunFilter,
+    ) -> impl Stream<Item = Result<Run, ActionsError>> {
+        let url = self.url(&format!("/repos/{repo}/actions/runs", repo = repository));
+        self.filtered_runs(&url, filter)
+    }
+
+    /// Pages through the runs listed at a url, newest first, until a page holds only runs
+    /// created before the filter's `since`
+    fn filtered_runs(
+        self,
+        url: &str,
+        filter: RunFilter,
+    ) -> impl Stream<Item = Result<Run, ActionsError>> {
+        let mut builder = self.get(url).query(&[("per_page", self.per_page)]);
+        if let Some(status) = &filter.status {
+            builder = builder.query(&[("status", status)]);
+        }
+        if let Some(event) = &filter.event {
+            builder = builder.query(&[("event", event)]);
+        }
+        let since = filter.since;
+        self.paginate(
+            PageState::Fetch(Box::new(builder)),
+            |w: Runs| w.workflow_runs,
+            move |runs: &Vec<Run>| runs.iter().any(|run| run.created_at >= since),
+        )
+        .try_filter(move |run| future::ready(filter.matches(run)))
+    }
+}
+
+/// Decodes base64 file contents, which the api wraps across lines
+fn decode_contents(content: &str) -> Result<String, ActionsError> {
+    let content = content
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    base64::decode(content)
+        .map_err(|err| ActionsError::Decode(err.into()))
+        .and_then(|bytes| String::from_utf8(bytes).map_err(|err| ActionsError::Decode(err.into())))
+}
+
+/// Extracts the api's error message from an unsuccessful response, falling back on its status
+async fn client_error(response: Response) -> String {
+    let status = response.status();
+    response
+        .json::<ClientError>()
+        .await
+        .map(|err| err.message)
+        .unwrap_or_else(|_| status.to_string())
+}
+
+/// Fields as query parameters, with strings unquoted
+fn query_params(fields: &[(String, Value)]) -> Vec<(&str, String)> {
+    fields
+        .iter()
+        .map(|(name, value)| match value {
+            Value::String(value) => (name.as_str(), value.clone()),
+            value => (name.as_str(), value.to_string()),
+        })
+        .collect()
+}
+
+/// Whether a response status indicates a failure likely to pass on retry
+fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Whether a request that failed without a response may be sent again. Requests that
+/// must not be applied twice are only retried when the connection itself could not be made
+fn is_retryable(
+    err: &reqwest::Error,
+    retry: Retry,
+) -> bool {
+    let mut source = err.source();
+    while let Some(cause) = source {
+        if let Some(io) = cause.downcast_ref::<io::Error>() {
+            return match io.kind() {
+                io::ErrorKind::ConnectionRefused | io::ErrorKind::NotConnected => true,
+                io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe
+                | io::ErrorKind::TimedOut => retry == Retry::Always,
+                _ => false,
+            };
+        }
+        source = cause.source();
+    }
+    retry == Retry::Always && err.is_timeout()
+}
+
+/// Exponential backoff starting at one second and capped at a minute, with up to
+/// half of each delay randomized by `jitter`, a fraction between 0 and 1
+fn backoff(
+    retries: usize,
+    jitter: f64,
+) -> Duration {
+    let delay = Duration::from_secs(1 << retries.min(6)).min(Duration::from_secs(60));
+    delay / 2 + (delay / 2).mul_f64(jitter.clamp(0.0, 1.0))
+}
+
+/// A cheap source of randomness for spreading out retries
+fn jitter() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| f64::from(now.subsec_nanos()) / 1_000_000_000.0)
+        .unwrap_or_default()
+}
+
+/// Returns how long to wait before retrying a response that was rate limited,
+/// based on its `Retry-After` or `X-RateLimit-Reset` headers
+fn rate_limit_delay(
+    response: &Response,
+    now: DateTime<Utc>,
+) -> Option<Duration> {
+    if response.status() != StatusCode::FORBIDDEN
+        && response.status() != StatusCode::TOO_MANY_REQUESTS
+    {
+        return None;
+    }
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    if let Some(seconds) = header("Retry-After").and_then(|value| value.parse().ok()) {
+        return Some(Duration::from_secs(seconds));
+    }
+    if header("X-RateLimit-Remaining") != Some("0") {
+        return None;
+    }
+    let reset = header("X-RateLimit-Reset")?.parse::<i64>().ok()?;
+    Some(Duration::from_secs((reset - now.timestamp()).max(1) as u64))
 }
 
 fn next_link(response: &Response) -> Option<String> {
@@ -490,6 +1813,549 @@ fn next_link(response: &Response) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures_await_test::async_test;
+    use hyper::{
+        body::to_bytes,
+        service::{make_service_fn, service_fn},
+        Body, Server,
+    };
+    use std::{
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex as SyncMutex,
+        },
+    };
+
+    /// A request received by the local api server
+    #[derive(Debug, Clone)]
+    struct MockRequest {
+        method: String,
+        path: String,
+        /// Header names and values, with names lowercased
+        headers: Vec<(String, String)>,
+        body: String,
+    }
+
+    impl MockRequest {
+        fn header(
+            &self,
+            name: &str,
+        ) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(header, _)| header == name)
+                .map(|(_, value)| value.as_str())
+        }
+    }
+
+    /// Starts a local api server which responds to each request with the status, extra headers
+    /// and json body returned by `route` for its base url and the request. Returns the server's
+    /// base url along with the requests received so far
+    fn mock_api(
+        route: impl Fn(&str, &MockRequest) -> (u16, String, String) + Send + Sync + 'static
+    ) -> (String, Arc<SyncMutex<Vec<MockRequest>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requested = Arc::new(SyncMutex::new(Vec::new()));
+        let (base, route, requests) = (base_url.clone(), Arc::new(route), requested.clone());
+        let service = make_service_fn(move |_| {
+            let (base, route, requests) = (base.clone(), route.clone(), requests.clone());
+            future::ok::<_, hyper::Error>(service_fn(move |request: hyper::Request<Body>| {
+                let (base, route, requests) = (base.clone(), route.clone(), requests.clone());
+                async move {
+                    let (parts, body) = request.into_parts();
+                    let request = MockRequest {
+                        method: parts.method.to_string(),
+                        path: parts.uri.to_string(),
+                        headers: parts
+                            .headers
+                            .iter()
+                            .map(|(name, value)| {
+                                (name.to_string(), value.to_str().unwrap().to_string())
+                            })
+                            .collect(),
+                        body: String::from_utf8(to_bytes(body).await?.to_vec()).unwrap(),
+                    };
+                    let (status, headers, body) = route(&base, &request);
+                    requests.lock().unwrap().push(request);
+                    let mut response = hyper::Response::builder()
+                        .status(status)
+                        .header("Content-Type", "application/json");
+                    for (name, value) in headers.lines().filter_map(|line| line.split_once(": ")) {
+                        response = response.header(name, value);
+                    }
+                    Ok::<_, hyper::Error>(response.body(Body::from(body)).unwrap())
+                }
+            }))
+        });
+        tokio::spawn(Server::from_tcp(listener).unwrap().serve(service));
+        (base_url, requested)
+    }
+
+    fn mock_requests(base_url: String) -> Requests {
+        Requests {
+            client: reqwest::Client::new(),
+            credentials: Credentials::Token("token".into()),
+            base_url,
+            max_retries: 0,
+            per_page: 1,
+            cache: None,
+            user_agent: DEFAULT_USER_AGENT.into(),
+            api_version: DEFAULT_API_VERSION.into(),
+            throttle: None,
+            dry_run: false,
+        }
+    }
+
+    fn next(
+        base: &str,
+        path: &str,
+    ) -> String {
+        format!("Link: <{}{}>; rel=\"next\"\r\n", base, path)
+    }
+
+    fn run_json(
+        id: usize,
+        created_at: &str,
+    ) -> String {
+        format!(
+            r#"{{"id":{id},"head_branch":"main","conclusion":"success","event":"push","status":"completed","jobs_url":"","logs_url":"","artifacts_url":"","cancel_url":"","rerun_url":"","created_at":"{at}","updated_at":"{at}","html_url":"","name":"ci","workflow_id":1}}"#,
+            id = id,
+            at = created_at
+        )
+    }
+
+    #[tokio::test]
+    async fn paginate_follows_next_links_until_the_last_page() {
+        let (base_url, requested) = mock_api(|base, request| {
+            match request.path.as_str() {
+            "/repos/owner/repo/actions/workflows?per_page=1" => (
+                200,
+                next(base, "/repos/owner/repo/actions/workflows?per_page=1&page=2"),
+                r#"{"workflows":[{"id":1,"name":"ci","state":"active","path":".github/workflows/ci.yml"}]}"#.into(),
+            ),
+            _ => (
+                200,
+                String::new(),
+                r#"{"workflows":[{"id":2,"name":"release","state":"active","path":".github/workflows/release.yml"}]}"#.into(),
+            ),
+        }
+        });
+        let names = mock_requests(base_url)
+            .workflows("owner/repo".into())
+            .map_ok(|workflow| workflow.name)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(names, vec!["ci", "release"]);
+        assert_eq!(
+            requested
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|request| request.path.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "/repos/owner/repo/actions/workflows?per_page=1",
+                "/repos/owner/repo/actions/workflows?per_page=1&page=2"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn paginate_retries_a_failing_middle_page() {
+        let attempts = AtomicUsize::new(0);
+        let (base_url, requested) = mock_api(move |base, request| {
+            match request.path.as_str() {
+            "/repos/owner/repo/actions/workflows?per_page=1" => (
+                200,
+                next(base, "/repos/owner/repo/actions/workflows?per_page=1&page=2"),
+                r#"{"workflows":[{"id":1,"name":"ci","state":"active","path":".github/workflows/ci.yml"}]}"#.into(),
+            ),
+            "/repos/owner/repo/actions/workflows?per_page=1&page=2" if attempts.fetch_add(1, Ordering::SeqCst) == 0 => (
+                500,
+                String::new(),
+                r#"{"message":"Server Error"}"#.into(),
+            ),
+            "/repos/owner/repo/actions/workflows?per_page=1&page=2" => (
+                200,
+                next(base, "/repos/owner/repo/actions/workflows?per_page=1&page=3"),
+                r#"{"workflows":[{"id":2,"name":"release","state":"active","path":".github/workflows/release.yml"}]}"#.into(),
+            ),
+            _ => (
+                200,
+                String::new(),
+                r#"{"workflows":[{"id":3,"name":"docs","state":"active","path":".github/workflows/docs.yml"}]}"#.into(),
+            ),
+        }
+        });
+        let names = mock_requests(base_url)
+            .workflows("owner/repo".into())
+            .map_ok(|workflow| workflow.name)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(names, vec!["ci", "release", "docs"]);
+        assert_eq!(requested.lock().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn paginate_does_not_retry_unrecoverable_pages() {
+        let (base_url, requested) = mock_api(|base, request| {
+            match request.path.as_str() {
+            "/repos/owner/repo/actions/workflows?per_page=1" => (
+                200,
+                next(base, "/repos/owner/repo/actions/workflows?per_page=1&page=2"),
+                r#"{"workflows":[{"id":1,"name":"ci","state":"active","path":".github/workflows/ci.yml"}]}"#.into(),
+            ),
+            _ => (404, String::new(), r#"{"message":"Not Found"}"#.into()),
+        }
+        });
+        let mut workflows = mock_requests(base_url)
+            .workflows("owner/repo".into())
+            .boxed();
+        assert!(workflows.try_next().await.is_ok());
+        assert!(matches!(
+            workflows.try_next().await,
+            Err(ActionsError::NotFound(_))
+        ));
+        assert_eq!(requested.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn cached_pages_are_revalidated_with_their_etags() {
+        let (base_url, requested) = mock_api(|base, request| {
+            match (request.path.as_str(), request.header("if-none-match")) {
+                ("/repos/owner/repo/actions/workflows?per_page=1", Some("\"v1\"")) => {
+                    (304, String::new(), String::new())
+                }
+                ("/repos/owner/repo/actions/workflows?per_page=1", _) => (
+                    200,
+                    format!(
+                        "ETag: \"v1\"\r\n{}",
+                        next(base, "/repos/owner/repo/actions/workflows?per_page=1&page=2")
+                    ),
+                    r#"{"workflows":[{"id":1,"name":"ci","state":"active","path":".github/workflows/ci.yml"}]}"#.into(),
+                ),
+                _ => (
+                    200,
+                    String::new(),
+                    r#"{"workflows":[{"id":2,"name":"release","state":"active","path":".github/workflows/release.yml"}]}"#.into(),
+                ),
+            }
+        });
+        let dir = std::env::temp_dir().join(format!("actions-revalidate-{}", std::process::id()));
+        let requests = Requests {
+            cache: Some(Cache::new(dir.clone())),
+            ..mock_requests(base_url)
+        };
+        for _ in 0..2 {
+            let names = requests
+                .clone()
+                .workflows("owner/repo".into())
+                .map_ok(|workflow| workflow.name)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            assert_eq!(names, vec!["ci", "release"]);
+        }
+        let _ = std::fs::remove_dir_all(dir);
+        assert_eq!(
+            requested
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|request| request.header("if-none-match"))
+                .collect::<Vec<_>>(),
+            vec![None, None, Some("\"v1\""), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn runs_stop_paging_once_a_page_is_older_than_since() {
+        let (base_url, requested) = mock_api(|base, request| {
+            let page = request.path.rsplit("page=").next().unwrap_or_default();
+            let path = "/repos/owner/repo/actions/workflows/1/runs?per_page=1&page=";
+            match page {
+                "1" => (
+                    200,
+                    next(base, &format!("{}2", path)),
+                    format!(
+                        r#"{{"workflow_runs":[{}]}}"#,
+                        run_json(1, "2020-03-02T00:00:00Z")
+                    ),
+                ),
+                _ => (
+                    200,
+                    next(base, &format!("{}3", path)),
+                    format!(
+                        r#"{{"workflow_runs":[{}]}}"#,
+                        run_json(2, "2020-02-28T00:00:00Z")
+                    ),
+                ),
+            }
+        });
+        let ids = mock_requests(base_url)
+            .runs(
+                "owner/repo".into(),
+                "1".into(),
+                RunFilter {
+                    since: "2020-03-01T00:00:00Z".parse().unwrap(),
+                    until: None,
+                    status: None,
+                    branch: None,
+                    event: None,
+                    actor: None,
+                },
+            )
+            .map_ok(|run| run.id)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(ids, vec![1]);
+        assert_eq!(requested.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn all_runs_lists_runs_of_every_workflow() {
+        let (base_url, requested) = mock_api(|_, _| {
+            (
+                200,
+                String::new(),
+                format!(
+                    r#"{{"workflow_runs":[{},{},{}]}}"#,
+                    run_json(1, "2020-03-02T00:00:00Z"),
+                    run_json(3, "2020-03-01T12:00:00Z").replace(r#""name":"ci""#, r#""name":null"#),
+                    run_json(2, "2020-02-28T00:00:00Z")
+                ),
+            )
+        });
+        let ids = mock_requests(base_url)
+            .all_runs(
+                "owner/repo".into(),
+                RunFilter {
+                    since: "2020-03-01T00:00:00Z".parse().unwrap(),
+                    until: None,
+                    status: Some("completed".into()),
+                    branch: None,
+                    event: None,
+                    actor: None,
+                },
+            )
+            .map_ok(|run| (run.id, run.workflow_name()))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(ids, vec![(1, "ci".to_string()), (3, "1".to_string())]);
+        assert_eq!(
+            requested.lock().unwrap()[0].path,
+            "/repos/owner/repo/actions/runs?per_page=1&status=completed"
+        );
+    }
+
+    #[tokio::test]
+    async fn upsert_secret_puts_the_encrypted_value_and_reports_creation() {
+        let (base_url, requested) =
+            mock_api(
+                |_, request| match request.path.rsplit('/').next().unwrap_or_default() {
+                    "NEW" => (201, String::new(), String::new()),
+                    "EXISTING" => (204, String::new(), String::new()),
+                    _ => (
+                        422,
+                        String::new(),
+                        r#"{"message":"Invalid request"}"#.into(),
+                    ),
+                },
+            );
+        let requests = mock_requests(base_url);
+        let upsert = |name: &str| {
+            requests.clone().upsert_secret(
+                SecretScope::Repo("owner/repo".into()),
+                name.into(),
+                SecretValue {
+                    encrypted_value: "c2VjcmV0".into(),
+                    key_id: "012345678912345678".into(),
+                    visibility: None,
+                    selected_repository_ids: None,
+                },
+            )
+        };
+        assert!(upsert("NEW").await.unwrap());
+        assert!(!upsert("EXISTING").await.unwrap());
+        assert!(upsert("INVALID").await.is_err());
+        let requested = requested.lock().unwrap();
+        assert_eq!(requested[0].method, "PUT");
+        assert_eq!(requested[0].path, "/repos/owner/repo/actions/secrets/NEW");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&requested[0].body).unwrap(),
+            serde_json::json!({
+                "encrypted_value": "c2VjcmV0",
+                "key_id": "012345678912345678"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn mutating_requests_are_authenticated() {
+        let (base_url, requested) = mock_api(|_, request| match request.method.as_str() {
+            "POST" => (201, String::new(), "{}".into()),
+            _ => (204, String::new(), String::new()),
+        });
+        let requests = mock_requests(base_url);
+        requests
+            .clone()
+            .rerun("owner/repo".into(), 1, false)
+            .await
+            .unwrap();
+        requests
+            .set_workflow_enabled("owner/repo", 2, true)
+            .await
+            .unwrap();
+        let requested = requested.lock().unwrap();
+        assert_eq!(
+            requested
+                .iter()
+                .map(|request| (request.method.as_str(), request.path.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("POST", "/repos/owner/repo/actions/runs/1/rerun"),
+                ("PUT", "/repos/owner/repo/actions/workflows/2/enable")
+            ]
+        );
+        for request in requested.iter() {
+            assert_eq!(request.header("authorization"), Some("bearer token"));
+            assert_eq!(request.header("user-agent"), Some(DEFAULT_USER_AGENT));
+            assert_eq!(
+                request.header("x-github-api-version"),
+                Some(DEFAULT_API_VERSION)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_send_the_configured_user_agent() {
+        let (base_url, requested) = mock_api(|_, _| (204, String::new(), String::new()));
+        Requests {
+            user_agent: "acme-audit/1.0".into(),
+            ..mock_requests(base_url)
+        }
+        .set_workflow_enabled("owner/repo", 2, false)
+        .await
+        .unwrap();
+        assert_eq!(
+            requested.lock().unwrap()[0].header("user-agent"),
+            Some("acme-audit/1.0")
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limited_deletes_are_retried_after_the_indicated_delay() {
+        let attempts = AtomicUsize::new(0);
+        let (base_url, requested) =
+            mock_api(move |_, _| match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 => (
+                    403,
+                    "Retry-After: 0\r\n".into(),
+                    r#"{"message":"You have exceeded a secondary rate limit"}"#.into(),
+                ),
+                _ => (204, String::new(), String::new()),
+            });
+        Requests {
+            max_retries: 1,
+            ..mock_requests(base_url)
+        }
+        .delete_artifact("owner/repo".into(), 1)
+        .await
+        .unwrap();
+        assert_eq!(requested.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn user_includes_the_tokens_scopes() {
+        let (base_url, _) = mock_api(|_, _| {
+            (
+                200,
+                "X-OAuth-Scopes: repo, workflow\r\n".into(),
+                r#"{"login":"octocat"}"#.into(),
+            )
+        });
+        let (user, scopes) = mock_requests(base_url).user().await.unwrap();
+        assert_eq!(user.login, "octocat");
+        assert_eq!(
+            scopes,
+            Some(vec!["repo".to_string(), "workflow".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn job_logs_are_downloaded_as_text() {
+        let (base_url, requested) = mock_api(|_, request| match request.path.as_str() {
+            "/repos/owner/repo/actions/jobs/3/logs" => {
+                (200, String::new(), "2020-03-12T10:00:00Z ok".into())
+            }
+            _ => (410, String::new(), String::new()),
+        });
+        let requests = mock_requests(base_url);
+        assert_eq!(
+            requests.job_logs("owner/repo", 3).await.unwrap(),
+            b"2020-03-12T10:00:00Z ok".to_vec()
+        );
+        assert!(matches!(
+            requests.job_logs("owner/repo", 4).await,
+            Err(ActionsError::Api {
+                status: StatusCode::GONE,
+                ..
+            })
+        ));
+        assert_eq!(requested.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn runs_not_found_yet_are_fetched_again() {
+        let attempts = AtomicUsize::new(0);
+        let (base_url, requested) =
+            mock_api(move |_, _| match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 => (404, String::new(), r#"{"message":"Not Found"}"#.into()),
+                _ => (200, String::new(), run_json(1, "2020-03-12T00:00:00Z")),
+            });
+        let run = Requests {
+            max_retries: 1,
+            ..mock_requests(base_url.clone())
+        }
+        .run("owner/repo", 1)
+        .await
+        .unwrap();
+        assert_eq!(run.id, 1);
+        assert_eq!(requested.lock().unwrap().len(), 2);
+
+        let (base_url, requested) =
+            mock_api(|_, _| (404, String::new(), r#"{"message":"Not Found"}"#.into()));
+        assert!(matches!(
+            mock_requests(base_url).run("owner/repo", 2).await,
+            Err(ActionsError::NotFound(_))
+        ));
+        assert_eq!(requested.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn dry_runs_send_no_changes() {
+        let (base_url, requested) = mock_api(|_, _| (204, String::new(), String::new()));
+        let requests = Requests {
+            dry_run: true,
+            ..mock_requests(base_url)
+        };
+        requests
+            .clone()
+            .delete_artifact("owner/repo".into(), 1)
+            .await
+            .unwrap();
+        requests
+            .set_workflow_enabled("owner/repo", 2, false)
+            .await
+            .unwrap();
+        assert!(requested.lock().unwrap().is_empty());
+    }
 
     #[test]
     fn parse_next_link_returns_none_when_link_is_absent() {
@@ -514,4 +2380,403 @@ mod tests {
             Some("https://api.github.com/test&page=2".into())
         )
     }
+
+    #[test]
+    fn parse_next_link_preserves_enterprise_hosts() {
+        assert_eq!(
+            next_link(&Response::from(
+                http::Response::builder()
+                    .header(
+                        "Link",
+                        r#"<https://github.mycorp.com/api/v3/test&page=2>; rel="next""#
+                    )
+                    .body(vec![])
+                    .unwrap()
+            )),
+            Some("https://github.mycorp.com/api/v3/test&page=2".into())
+        )
+    }
+
+    #[test]
+    fn url_resolves_paths_relative_to_base_url() {
+        let requests = Requests {
+            client: reqwest::Client::new(),
+            credentials: Credentials::Token("token".into()),
+            base_url: "https://github.mycorp.com/api/v3/".into(),
+            max_retries: 0,
+            per_page: 100,
+            cache: None,
+            user_agent: DEFAULT_USER_AGENT.into(),
+            api_version: DEFAULT_API_VERSION.into(),
+            throttle: None,
+            dry_run: false,
+        };
+        assert_eq!(
+            requests.url("/repos/owner/repo/actions/workflows"),
+            "https://github.mycorp.com/api/v3/repos/owner/repo/actions/workflows"
+        )
+    }
+
+    #[test]
+    fn decode_contents_ignores_line_breaks() {
+        assert_eq!(
+            decode_contents("bmFtZTogQ0kKb246IHB1\nc2gK\n").unwrap(),
+            "name: CI\non: push\n"
+        );
+    }
+
+    #[test]
+    fn code_search_is_incomplete_past_the_result_limit() {
+        let search = |total_count: usize, incomplete_results: bool| {
+            serde_json::from_value::<CodeSearch>(serde_json::json!({
+                "total_count": total_count,
+                "incomplete_results": incomplete_results,
+                "items": []
+            }))
+            .unwrap()
+        };
+        assert!(!search(1000, false).incomplete());
+        assert!(search(1001, false).incomplete());
+        assert!(search(10, true).incomplete());
+    }
+
+    #[test]
+    fn workflow_usage_deserializes_billable_timing() {
+        let usage: WorkflowUsage = serde_json::from_str(
+            r#"{"billable":{"UBUNTU":{"total_ms":180000},"MACOS":{"total_ms":240000}}}"#,
+        )
+        .unwrap();
+        assert_eq!(usage.ubuntu(), Duration::from_secs(180));
+        assert_eq!(usage.macos(), Duration::from_secs(240));
+        assert_eq!(usage.windows(), Duration::default());
+    }
+
+    #[test]
+    fn query_params_unquote_strings() {
+        assert_eq!(
+            query_params(&[
+                ("status".into(), Value::String("completed".into())),
+                ("per_page".into(), serde_json::json!(5))
+            ]),
+            vec![
+                ("status", "completed".to_string()),
+                ("per_page", "5".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn key_deserializes_public_key_response() {
+        let key: Key = serde_json::from_str(
+            r#"{"key_id": "012345678912345678", "key": "2Sg8iYjAxxmI2LvUXpJjkYrMxURPc8r+dB7TJyvv1234"}"#,
+        )
+        .unwrap();
+        assert_eq!(key.key_id, "012345678912345678");
+        assert_eq!(key.key, "2Sg8iYjAxxmI2LvUXpJjkYrMxURPc8r+dB7TJyvv1234");
+        assert_eq!(
+            serde_json::to_string(&key).unwrap(),
+            r#"{"key_id":"012345678912345678","key":"2Sg8iYjAxxmI2LvUXpJjkYrMxURPc8r+dB7TJyvv1234"}"#
+        );
+    }
+
+    #[test]
+    fn actions_caches_keep_their_ref_field_name() {
+        let caches: ActionsCaches = serde_json::from_str(
+            r#"{"total_count": 1, "actions_caches": [{"id": 505, "ref": "refs/heads/main", "key": "Linux-node-958aff96db2d75d67787d1e634ae70b659de937b", "version": "73885106f58cc52a7df9ec4d4a5622a5614813162cb516c1aa1e", "last_accessed_at": "2019-01-24T22:45:36.000Z", "created_at": "2019-01-24T22:45:36.000Z", "size_in_bytes": 1024}]}"#,
+        )
+        .unwrap();
+        let cache = &caches.actions_caches[0];
+        assert_eq!(cache.git_ref, "refs/heads/main");
+        assert_eq!(cache.size_in_bytes, 1024);
+        assert!(serde_json::to_string(cache)
+            .unwrap()
+            .contains(r#""ref":"refs/heads/main""#));
+    }
+
+    #[test]
+    fn installation_token_expires_early() {
+        let token = InstallationToken {
+            token: "ghs_token".into(),
+            expires_at: "2020-01-01T01:00:00Z".parse().unwrap(),
+        };
+        assert!(token.valid_at("2020-01-01T00:54:59Z".parse().unwrap()));
+        assert!(!token.valid_at("2020-01-01T00:55:00Z".parse().unwrap()));
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_with_bounded_jitter() {
+        assert_eq!(backoff(0, 0.0), Duration::from_millis(500));
+        assert_eq!(backoff(0, 1.0), Duration::from_secs(1));
+        assert_eq!(backoff(3, 0.0), Duration::from_secs(4));
+        assert_eq!(backoff(3, 1.0), Duration::from_secs(8));
+        assert_eq!(backoff(20, 1.0), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn is_transient_only_matches_gateway_failures() {
+        assert!(is_transient(StatusCode::BAD_GATEWAY));
+        assert!(is_transient(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_transient(StatusCode::GATEWAY_TIMEOUT));
+        assert!(!is_transient(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_transient(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn rate_limit_delay_ignores_successful_responses() {
+        assert_eq!(
+            rate_limit_delay(
+                &Response::from(
+                    http::Response::builder()
+                        .header("Retry-After", "30")
+                        .body(vec![])
+                        .unwrap()
+                ),
+                Utc::now()
+            ),
+            None
+        )
+    }
+
+    #[test]
+    fn rate_limit_delay_prefers_retry_after() {
+        assert_eq!(
+            rate_limit_delay(
+                &Response::from(
+                    http::Response::builder()
+                        .status(403)
+                        .header("Retry-After", "30")
+                        .body(vec![])
+                        .unwrap()
+                ),
+                Utc::now()
+            ),
+            Some(Duration::from_secs(30))
+        )
+    }
+
+    #[test]
+    fn rate_limit_delay_waits_until_reset() {
+        let now = Utc::now();
+        assert_eq!(
+            rate_limit_delay(
+                &Response::from(
+                    http::Response::builder()
+                        .status(403)
+                        .header("X-RateLimit-Remaining", "0")
+                        .header("X-RateLimit-Reset", (now.timestamp() + 60).to_string())
+                        .body(vec![])
+                        .unwrap()
+                ),
+                now
+            ),
+            Some(Duration::from_secs(60))
+        )
+    }
+
+    #[async_test]
+    async fn github_error_distinguishes_auth_and_missing_resources() {
+        let unauthorized = ActionsError::from_response(Response::from(
+            http::Response::builder()
+                .status(401)
+                .body(r#"{"message":"Bad credentials"}"#)
+                .unwrap(),
+        ))
+        .await;
+        assert!(
+            matches!(unauthorized, ActionsError::Auth(message) if message == "Bad credentials")
+        );
+        let missing = ActionsError::from_response(Response::from(
+            http::Response::builder().status(404).body("").unwrap(),
+        ))
+        .await;
+        assert!(matches!(missing, ActionsError::NotFound(_)));
+        let limited = ActionsError::from_response(Response::from(
+            http::Response::builder()
+                .status(429)
+                .header("Retry-After", "90")
+                .body(r#"{"message":"API rate limit exceeded"}"#)
+                .unwrap(),
+        ))
+        .await;
+        assert_eq!(
+            limited.to_string(),
+            "rate limit exceeded. Try again in 1m 30s"
+        );
+    }
+
+    #[async_test]
+    async fn github_error_detects_missing_fine_grained_permissions() {
+        let forbidden = ActionsError::from_response(Response::from(
+            http::Response::builder()
+                .status(403)
+                .header("x-accepted-github-permissions", "secrets=read")
+                .body(r#"{"message":"Resource not accessible by personal access token"}"#)
+                .unwrap(),
+        ))
+        .await;
+        assert!(matches!(
+            &forbidden,
+            ActionsError::MissingPermission { accepted: Some(accepted), .. } if accepted == "secrets=read"
+        ));
+        assert_eq!(
+            forbidden.to_string(),
+            "the token lacks a required permission: Resource not accessible by personal access token. Accepted permissions: secrets=read"
+        );
+        let integration = ActionsError::from_response(Response::from(
+            http::Response::builder()
+                .status(403)
+                .body(r#"{"message":"Resource not accessible by integration"}"#)
+                .unwrap(),
+        ))
+        .await;
+        assert!(matches!(
+            integration,
+            ActionsError::MissingPermission { accepted: None, .. }
+        ));
+        let denied = ActionsError::from_response(Response::from(
+            http::Response::builder()
+                .status(403)
+                .body(r#"{"message":"Must have admin rights to Repository."}"#)
+                .unwrap(),
+        ))
+        .await;
+        assert!(matches!(denied, ActionsError::Auth(_)));
+    }
+
+    #[test]
+    fn workflow_filename_is_last_path_segment() {
+        let workflow = Workflow {
+            id: 1,
+            name: "CI".into(),
+            state: "active".into(),
+            path: ".github/workflows/ci.yml".into(),
+        };
+        assert_eq!(workflow.filename(), "ci.yml")
+    }
+
+    #[test]
+    fn only_active_workflows_are_active() {
+        let workflow = |state: &str| Workflow {
+            id: 1,
+            name: "CI".into(),
+            state: state.into(),
+            path: ".github/workflows/ci.yml".into(),
+        };
+        assert!(workflow("active").active());
+        assert!(!workflow("disabled_manually").active());
+        assert!(!workflow("deleted").active());
+    }
+
+    #[test]
+    fn secret_scope_paths() {
+        assert_eq!(
+            SecretScope::Repo("owner/repo".into()).path(),
+            "/repos/owner/repo/actions/secrets"
+        );
+        assert_eq!(
+            SecretScope::Org("owner".into()).path(),
+            "/orgs/owner/actions/secrets"
+        );
+        assert_eq!(
+            SecretScope::Environment {
+                repository_id: 42,
+                name: "production".into()
+            }
+            .path(),
+            "/repositories/42/environments/production/secrets"
+        );
+    }
+
+    fn run(
+        created_at: &str,
+        updated_at: &str,
+    ) -> Run {
+        Run {
+            id: 1,
+            head_branch: "main".into(),
+            conclusion: None,
+            event: "push".into(),
+            status: "completed".into(),
+            jobs_url: String::new(),
+            logs_url: String::new(),
+            artifacts_url: String::new(),
+            cancel_url: String::new(),
+            rerun_url: String::new(),
+            created_at: created_at.parse().unwrap(),
+            updated_at: updated_at.parse().unwrap(),
+            html_url: String::new(),
+            actor: None,
+            name: Some("ci".into()),
+            workflow_id: 1,
+        }
+    }
+
+    #[test]
+    fn run_duration_measures_created_to_updated() {
+        assert_eq!(
+            run("2020-01-01T00:00:00Z", "2020-01-01T00:01:30Z").duration(),
+            Duration::from_secs(90)
+        );
+    }
+
+    #[test]
+    fn run_created_within_includes_since_and_excludes_until() {
+        let since = "2020-01-01T00:00:00Z".parse().unwrap();
+        let until = "2020-02-01T00:00:00Z".parse().unwrap();
+        let created = |at| run(at, at);
+        assert!(created("2020-01-01T00:00:00Z").created_within(since, Some(until)));
+        assert!(created("2020-01-31T23:59:59Z").created_within(since, Some(until)));
+        assert!(!created("2020-02-01T00:00:00Z").created_within(since, Some(until)));
+        assert!(!created("2019-12-31T23:59:59Z").created_within(since, Some(until)));
+        assert!(created("2021-01-01T00:00:00Z").created_within(since, None));
+    }
+
+    #[test]
+    fn run_filter_matches_branch_ignoring_case_and_event_exactly() {
+        let filter = RunFilter {
+            since: "2020-01-01T00:00:00Z".parse().unwrap(),
+            until: None,
+            status: None,
+            branch: Some("Main".into()),
+            event: Some("push".into()),
+            actor: None,
+        };
+        let mut run = run("2020-01-02T00:00:00Z", "2020-01-02T00:00:00Z");
+        assert!(filter.matches(&run));
+        run.event = "pull_request".into();
+        assert!(!filter.matches(&run));
+        run.event = "push".into();
+        run.head_branch = "feature".into();
+        assert!(!filter.matches(&run));
+    }
+
+    #[test]
+    fn run_filter_matches_actor_ignoring_case() {
+        let filter = RunFilter {
+            since: "2020-01-01T00:00:00Z".parse().unwrap(),
+            until: None,
+            status: None,
+            branch: None,
+            event: None,
+            actor: Some("Octocat".into()),
+        };
+        let mut run = run("2020-01-02T00:00:00Z", "2020-01-02T00:00:00Z");
+        assert!(!filter.matches(&run));
+        run.actor = Some(User {
+            login: "octocat".into(),
+        });
+        assert!(filter.matches(&run));
+        run.actor = Some(User {
+            login: "hubot".into(),
+        });
+        assert!(!filter.matches(&run));
+    }
+
+    #[test]
+    fn run_duration_is_zero_when_updated_before_created() {
+        assert_eq!(
+            run("2020-01-01T00:01:30Z", "2020-01-01T00:00:00Z").duration(),
+            Duration::default()
+        );
+    }
 }
@@ -1,18 +1,45 @@
+mod api;
 mod artifacts;
+mod cache;
+mod caches;
+mod format;
+mod git;
+mod permissions;
 mod repos;
 mod runs;
 mod secrets;
+mod throttle;
+mod token;
+mod whoami;
 mod workflows;
+use api::{api, Api};
 use artifacts::{artifacts, Artifacts};
+use caches::{caches, Caches};
 use repos::{repos, Repos};
 use runs::{runs, Runs};
 use secrets::{secrets, Secrets};
 use std::error::Error;
-use structopt::StructOpt;
+use structopt::{
+    clap::{AppSettings, Shell},
+    StructOpt,
+};
+use whoami::whoami;
 use workflows::{workflows, Workflows};
 mod github;
 use colored::Colorize;
-use std::{fmt, process::exit};
+use format::{Fields, Format, Table};
+use futures::{Stream, TryStreamExt};
+use github::{ActionsError, Credentials, Requests};
+use indicatif::ProgressBar;
+use serde::Serialize;
+use std::{
+    env, fmt,
+    io::{stderr, stdin, stdout, IsTerminal, Write},
+    path::PathBuf,
+    process::exit,
+    str::FromStr,
+    time::Duration,
+};
 
 #[derive(Debug)]
 struct StringErr(String);
@@ -28,31 +55,485 @@ impl fmt::Display for StringErr {
     }
 }
 
+/// Error of a command which completed but found failing runs. It exits with status 2,
+/// unlike other errors such as failed requests which exit with status 1
+#[derive(Debug)]
+pub struct Failure(String);
+
+impl Error for Failure {}
+
+impl fmt::Display for Failure {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Asks a yes or no question on stderr, answered with a line of stdin. Anything but
+/// y or yes is taken as no. Fails rather than waiting on an answer that can't be given
+/// when stdin is not a terminal
+pub fn confirm(question: &str) -> Result<bool, Box<dyn Error>> {
+    if !stdin().is_terminal() {
+        return Err(StringErr(format!(
+            "{} can't be confirmed as stdin is not a terminal. Pass --yes to confirm",
+            question
+        ))
+        .into());
+    }
+    eprint!("{} [y/N] ", question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Output mode for listing commands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Output {
+    Text,
+    Json,
+    JsonLines,
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Output::Text),
+            "json" => Ok(Output::Json),
+            "jsonl" => Ok(Output::JsonLines),
+            other => Err(format!(
+                "{} is not a supported output. try 'text', 'json' or 'jsonl' instead",
+                other
+            )),
+        }
+    }
+}
+
+/// Writes a list of items to stdout as a json array
+pub fn print_json<T: Serialize>(items: &[T]) -> Result<(), Box<dyn Error>> {
+    let stdout = stdout();
+    let mut out = stdout.lock();
+    serde_json::to_writer_pretty(&mut out, items)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Writes each item of a stream to stdout as a line of json as soon as it arrives
+pub async fn print_json_lines<T, E, S>(mut items: S) -> Result<(), Box<dyn Error>>
+where
+    T: Serialize,
+    E: Into<Box<dyn Error>>,
+    S: Stream<Item = Result<T, E>> + Unpin,
+{
+    while let Some(item) = items.try_next().await.map_err(Into::into)? {
+        print_json_line(&item)?;
+    }
+    Ok(())
+}
+
+/// Writes a single item to stdout as a line of json, flushing it immediately
+pub fn print_json_line<T: Serialize>(item: &T) -> Result<(), Box<dyn Error>> {
+    let stdout = stdout();
+    let mut out = stdout.lock();
+    serde_json::to_writer(&mut out, item)?;
+    writeln!(out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Writes only the selected fields of each item of a stream as it arrives, in any output
+pub async fn print_fields<T, E, S>(
+    config: &Config,
+    fields: &Fields,
+    format: Format,
+    mut items: S,
+) -> Result<(), Box<dyn Error>>
+where
+    T: Serialize,
+    E: Into<Box<dyn Error>>,
+    S: Stream<Item = Result<T, E>> + Unpin,
+{
+    let mut table = match config.output {
+        Output::Text => Some(Table::with_pager(format, config.pager()).quiet(config.quiet)),
+        _ => None,
+    };
+    if let Some(table) = &mut table {
+        table.header_cells(fields.names())?;
+    }
+    let mut listed = Vec::new();
+    while let Some(item) = items.try_next().await.map_err(Into::into)? {
+        match &mut table {
+            Some(table) => table.cells(&fields.cells(&item)?)?,
+            None if config.output == Output::JsonLines => print_json_line(&fields.object(&item)?)?,
+            None => listed.push(fields.object(&item)?),
+        }
+    }
+    match table {
+        Some(mut table) => table.flush()?,
+        None if config.output == Output::Json => print_json(&listed)?,
+        None => (),
+    }
+    Ok(())
+}
+
+/// Options shared by all subcommands
+#[derive(Debug, StructOpt, Clone)]
+pub struct Config {
+    /// GitHub API base url. Set this to your instance's api url when using GitHub Enterprise Server
+    #[structopt(
+        long,
+        env = "GITHUB_API_URL",
+        default_value = github::DEFAULT_BASE_URL,
+        global = true
+    )]
+    pub api_url: String,
+    /// GitHub repository in the form owner/repo, for commands acting on a repository.
+    /// Defaults to the origin remote of the current git checkout
+    #[structopt(short, long, env = "ACTIONS_REPOSITORY", global = true)]
+    pub repository: Option<String>,
+    /// Output of listing commands 'text' (default), 'json' or 'jsonl', which prints each result
+    /// on its own line as it arrives. Must precede the subcommand
+    #[structopt(short, long, default_value = "text", env = "ACTIONS_OUTPUT")]
+    pub output: Output,
+    /// File containing a GitHub token, used when no GITHUB_TOKEN env variable is set
+    #[structopt(long, parse(from_os_str), global = true)]
+    pub token_file: Option<PathBuf>,
+    /// Read a GitHub token from the first line of stdin, in place of GITHUB_TOKEN.
+    /// Can't be combined with options which read other values from stdin, like --value-stdin
+    #[structopt(long, global = true)]
+    pub token_stdin: bool,
+    /// Token read from stdin with --token-stdin
+    #[structopt(skip)]
+    token: Option<String>,
+    /// Id of a GitHub App to authenticate as, in place of a token. Requires --installation-id and --private-key-file
+    #[structopt(long, env = "GITHUB_APP_ID", global = true)]
+    pub app_id: Option<u64>,
+    /// Id of the GitHub App's installation whose access tokens are used
+    #[structopt(long, env = "GITHUB_APP_INSTALLATION_ID", global = true)]
+    pub installation_id: Option<u64>,
+    /// PEM file containing the GitHub App's private key
+    #[structopt(
+        long,
+        parse(from_os_str),
+        env = "GITHUB_APP_PRIVATE_KEY_FILE",
+        global = true
+    )]
+    pub private_key_file: Option<PathBuf>,
+    /// Maximum number of times a rate limited or transiently failing request is retried. Runs
+    /// fetched by id, as by runs view and runs watch, are also fetched again this many times
+    /// while they aren't found, as a run created moments ago may not be found yet
+    #[structopt(long, default_value = "3", env = "ACTIONS_MAX_RETRIES", global = true)]
+    pub max_retries: usize,
+    /// Number of results to request per page, from 1 to 100
+    #[structopt(long, default_value = "100", parse(try_from_str = parse_per_page), global = true)]
+    pub per_page: usize,
+    /// Maximum number of results to list
+    #[structopt(long, global = true)]
+    pub limit: Option<usize>,
+    /// Maximum number of requests made at once when fetching or updating many resources
+    #[structopt(long, default_value = "20", parse(try_from_str = parse_concurrency), global = true)]
+    pub concurrency: usize,
+    /// Maximum number of api requests sent per second, i.e. 10 or 0.5. Requests are spaced
+    /// out to stay under it rather than waiting on GitHub's rate limits once they're hit
+    #[structopt(long, env = "ACTIONS_MAX_RPS", parse(try_from_str = parse_max_rps), global = true)]
+    pub max_rps: Option<f64>,
+    /// Seconds to wait for an api request to complete. Artifact and log downloads may take longer
+    #[structopt(
+        long,
+        default_value = "30",
+        env = "ACTIONS_TIMEOUT_SECS",
+        global = true
+    )]
+    pub timeout_secs: u64,
+    /// Log each api request and its response status to stderr. Authorization headers are never logged
+    #[structopt(long, global = true)]
+    pub verbose: bool,
+    /// Disable colored output. Colors are also disabled when a NO_COLOR env variable is set
+    /// or output is not a terminal
+    #[structopt(long, global = true)]
+    pub no_color: bool,
+    /// Page long listings of repos, workflows and runs through $PAGER, or `less -R` when no
+    /// PAGER is set, when output is a terminal
+    #[structopt(long, global = true)]
+    pub pager: bool,
+    /// Never page output. Takes precedence over --pager
+    #[structopt(long, global = true)]
+    pub no_pager: bool,
+    /// Don't cache listings under ~/.cache/actions-cli. Cached listings are revalidated with
+    /// conditional requests, which don't count against the rate limit when nothing changed
+    #[structopt(long, global = true)]
+    pub no_cache: bool,
+    /// Comma separated fields of each listed item to output, in order, i.e. id,name. Fields
+    /// are named as in json output. Tables list only these fields, without summaries
+    #[structopt(long, global = true)]
+    pub fields: Option<Fields>,
+    /// strftime format of times in tab and csv output, i.e. '%Y-%m-%d %H:%M'. Defaults to
+    /// RFC 3339, which json output always uses. --time-format is a deprecated alias
+    #[structopt(
+        long,
+        alias = "time-format",
+        env = "ACTIONS_DATE_FORMAT",
+        parse(try_from_str = runs::parse_time_format),
+        global = true
+    )]
+    pub date_format: Option<String>,
+    /// User-Agent header to identify requests by, i.e. to proxies and audit logs
+    #[structopt(
+        long,
+        env = "ACTIONS_USER_AGENT",
+        default_value = github::DEFAULT_USER_AGENT,
+        global = true
+    )]
+    pub user_agent: String,
+    /// Version of the REST API to request, sent as the X-GitHub-Api-Version header
+    #[structopt(
+        long,
+        env = "ACTIONS_API_VERSION",
+        default_value = github::DEFAULT_API_VERSION,
+        global = true
+    )]
+    pub api_version: String,
+    /// Print the requests which would change anything, i.e. create secrets or delete runs,
+    /// without sending them. Requests which only read are still sent
+    #[structopt(long, global = true)]
+    pub dry_run: bool,
+    /// Only print listed rows, leaving out header rows, summaries such as totals and the
+    /// progress of long running scans, i.e. discovering an organization's repos
+    #[structopt(short, long, global = true)]
+    pub quiet: bool,
+}
+
+fn parse_per_page(per_page: &str) -> Result<usize, String> {
+    match per_page.parse() {
+        Ok(per_page @ 1..=100) => Ok(per_page),
+        _ => Err(format!(
+            "{} is not a valid page size. try a number from 1 to 100 instead",
+            per_page
+        )),
+    }
+}
+
+fn parse_concurrency(concurrency: &str) -> Result<usize, String> {
+    match concurrency.parse() {
+        Ok(concurrency) if concurrency > 0 => Ok(concurrency),
+        _ => Err(format!(
+            "{} is not a valid concurrency. try a number greater than 0 instead",
+            concurrency
+        )),
+    }
+}
+
+fn parse_max_rps(max_rps: &str) -> Result<f64, String> {
+    match max_rps.parse::<f64>() {
+        Ok(max_rps) if max_rps > 0.0 && max_rps.is_finite() => Ok(max_rps),
+        _ => Err(format!(
+            "{} is not a valid request rate. try a number greater than 0 instead",
+            max_rps
+        )),
+    }
+}
+
+impl Config {
+    /// Creates an authenticated GitHub api client
+    pub fn requests(&self) -> Result<Requests, Box<dyn Error>> {
+        let credentials = match (self.app_id, self.installation_id, &self.private_key_file) {
+            (None, None, None) => Credentials::Token(match &self.token {
+                Some(token) => token.clone(),
+                None => token::resolve_token(self.token_file.as_deref(), &self.api_url)?,
+            }),
+            (Some(app_id), Some(installation_id), Some(private_key_file)) => Credentials::app(
+                token::App::new(app_id, installation_id, private_key_file)?,
+            ),
+            _ => {
+                return Err(StringErr(
+                    "Please provide all of --app-id, --installation-id and --private-key-file to authenticate as a GitHub App".into(),
+                )
+                .into())
+            }
+        };
+        Ok(Requests {
+            client: github::build_client(Duration::from_secs(self.timeout_secs))?,
+            credentials,
+            base_url: self.api_url.clone(),
+            max_retries: self.max_retries,
+            per_page: self.per_page,
+            cache: if self.no_cache {
+                None
+            } else {
+                cache::Cache::open()
+            },
+            user_agent: self.user_agent.clone(),
+            api_version: self.api_version.clone(),
+            throttle: self.max_rps.map(throttle::Throttle::new),
+            dry_run: self.dry_run,
+        })
+    }
+
+    /// Repository to act on, falling back to the origin remote of the current git checkout
+    pub fn repository(&self) -> Result<String, Box<dyn Error>> {
+        self.repository
+            .clone()
+            .or_else(|| git::detect_repository(&self.api_url))
+            .ok_or_else(|| {
+                StringErr("Please provide a --repository in the form owner/repo".into()).into()
+            })
+    }
+
+    /// Maximum number of results to list
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(usize::MAX)
+    }
+
+    /// Prints what a command changed. Dry runs change nothing, so only their planned
+    /// requests are printed
+    pub fn changed(
+        &self,
+        message: impl fmt::Display,
+    ) {
+        if !self.dry_run {
+            println!("{}", message);
+        }
+    }
+
+    /// Whether listings should be paged
+    pub fn pager(&self) -> bool {
+        self.pager && !self.no_pager && stdout().is_terminal()
+    }
+
+    /// Spinner on stderr showing the progress of a long running scan. Hidden with --quiet or
+    /// when stderr is not a terminal
+    pub fn progress(
+        &self,
+        message: &str,
+    ) -> ProgressBar {
+        if self.quiet || !stderr().is_terminal() {
+            return ProgressBar::hidden();
+        }
+        let progress = ProgressBar::new_spinner();
+        progress.set_message(message);
+        progress.enable_steady_tick(100);
+        progress
+    }
+
+    /// Whether output should be colored. A CLICOLOR_FORCE env variable keeps colors on
+    /// when output is not a terminal
+    fn color(&self) -> bool {
+        !self.no_color
+            && env::var_os("NO_COLOR").is_none()
+            && (stdout().is_terminal() || env::var_os("CLICOLOR_FORCE").is_some())
+    }
+}
+
 /// 🎬 GitHub actions cli
 ///
-/// A `GITHUB_TOKEN` env variable is required
-/// to authenticate with the GitHub's actions API
+/// A GitHub token is required to authenticate with the GitHub's actions API.
+/// It is read from stdin with `--token-stdin`, then a `GITHUB_TOKEN` env variable,
+/// then a `--token-file`, then the gh cli's stored credentials.
+///
+/// Exits with status 1 when a command fails, i.e. GitHub could not be reached, and with
+/// status 2 when a command completes but finds failing runs, i.e. `runs list --fail-on-failure`.
+///
+/// Commands acting on a repository default to the `origin` remote of the current
+/// git checkout when neither `--repository` nor `ACTIONS_REPOSITORY` is provided
 #[derive(Debug, StructOpt)]
-enum Options {
+struct Options {
+    #[structopt(flatten)]
+    config: Config,
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    Api(Api),
     Artifacts(Artifacts),
+    Caches(Caches),
     Repos(Repos),
     Runs(Runs),
     Secrets(Secrets),
     Workflows(Workflows),
+    /// 👤 Check the token, printing the user it authenticates as and its scopes
+    Whoami,
+    /// Generate shell completions, i.e. `source <(actions completions zsh)`
+    #[structopt(setting = AppSettings::Hidden)]
+    Completions {
+        /// Shell to generate completions for
+        #[structopt(possible_values = &Shell::variants())]
+        shell: Shell,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    pretty_env_logger::init();
-    if let Err(msg) = match Options::from_args() {
-        Options::Artifacts(args) => artifacts(args).await,
-        Options::Repos(args) => repos(args).await,
-        Options::Runs(args) => runs(args).await,
-        Options::Secrets(args) => secrets(args).await,
-        Options::Workflows(args) => workflows(args).await,
-    } {
+    let Options { config, command } = Options::from_args();
+    let mut logger = pretty_env_logger::formatted_builder();
+    if let Ok(filters) = env::var("RUST_LOG") {
+        logger.parse_filters(&filters);
+    }
+    if config.verbose {
+        logger.filter_module(module_path!(), log::LevelFilter::Debug);
+    }
+    logger.init();
+    if !config.color() {
+        colored::control::set_override(false);
+    }
+    let permission = permissions::required(&command);
+    let dry_run = config.dry_run;
+    let result = tokio::select! {
+        result = run(command, config) => result,
+        _ = tokio::signal::ctrl_c() => {
+            // by now the command's future is dropped, cancelling its requests and
+            // flushing the rows it had listed
+            exit(130)
+        }
+    };
+    if dry_run {
+        eprintln!("{}", "Dry run: no changes were made".yellow());
+    }
+    if let Err(msg) = result {
         eprintln!("{}: {}", "error".bold().red(), msg);
-        exit(1);
+        if let (Some(ActionsError::MissingPermission { .. }), Some((command, permission))) =
+            (msg.downcast_ref::<ActionsError>(), permission)
+        {
+            eprintln!("{} requires the {}", command, permission);
+        }
+        exit(if msg.is::<Failure>() { 2 } else { 1 });
     }
     Ok(())
 }
+
+async fn run(
+    command: Command,
+    mut config: Config,
+) -> Result<(), Box<dyn Error>> {
+    if config.token_stdin {
+        if let Command::Secrets(Secrets::Create {
+            value_stdin: true, ..
+        }) = command
+        {
+            return Err(StringErr(
+                "--token-stdin and --value-stdin can't both read from stdin".into(),
+            )
+            .into());
+        }
+        config.token = Some(token::read_token(stdin().lock())?);
+    }
+    match command {
+        Command::Api(args) => api(args, config).await,
+        Command::Artifacts(args) => artifacts(args, config).await,
+        Command::Caches(args) => caches(args, config).await,
+        Command::Repos(args) => repos(args, config).await,
+        Command::Runs(args) => runs(args, config).await,
+        Command::Secrets(args) => secrets(args, config).await,
+        Command::Workflows(args) => workflows(args, config).await,
+        Command::Whoami => whoami(config).await,
+        Command::Completions { shell } => {
+            Options::clap().gen_completions_to(env!("CARGO_PKG_NAME"), shell, &mut stdout());
+            Ok(())
+        }
+    }
+}
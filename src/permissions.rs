@@ -0,0 +1,178 @@
+//! Fine-grained token permissions required by each command, used to explain permission errors
+use crate::{
+    artifacts::Artifacts,
+    caches::Caches,
+    repos::{RepoCommand, Repos},
+    runs::Runs,
+    secrets::Secrets,
+    workflows::Workflows,
+    Command,
+};
+use std::fmt;
+
+/// A fine-grained token permission, i.e. the 'Actions' repository permission with read access
+#[derive(Debug, PartialEq)]
+pub struct Permission {
+    name: &'static str,
+    organization: bool,
+    write: bool,
+}
+
+impl fmt::Display for Permission {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "'{}' {} permission with {} access",
+            self.name,
+            if self.organization {
+                "organization"
+            } else {
+                "repository"
+            },
+            if self.write { "read and write" } else { "read" }
+        )
+    }
+}
+
+const fn repository(
+    name: &'static str,
+    write: bool,
+) -> Permission {
+    Permission {
+        name,
+        organization: false,
+        write,
+    }
+}
+
+/// Name of a command along with the permission it requires, for commands which require
+/// a single permission
+pub fn required(command: &Command) -> Option<(&'static str, Permission)> {
+    let required = match command {
+        Command::Artifacts(Artifacts::List { .. }) => {
+            ("artifacts list", repository("Actions", false))
+        }
+        Command::Artifacts(Artifacts::Download { .. }) => {
+            ("artifacts download", repository("Actions", false))
+        }
+        Command::Artifacts(Artifacts::DownloadAll { .. }) => {
+            ("artifacts download-all", repository("Actions", false))
+        }
+        Command::Artifacts(Artifacts::Size { .. }) => {
+            ("artifacts size", repository("Actions", false))
+        }
+        Command::Artifacts(Artifacts::Delete { .. }) => {
+            ("artifacts delete", repository("Actions", true))
+        }
+        Command::Artifacts(Artifacts::Prune { .. }) => {
+            ("artifacts prune", repository("Actions", true))
+        }
+        Command::Caches(Caches::List { .. }) => ("caches list", repository("Actions", false)),
+        Command::Caches(Caches::Delete { .. }) => ("caches delete", repository("Actions", true)),
+        Command::Repos(Repos {
+            command: Some(RepoCommand::Dispatch { .. }),
+            ..
+        }) => ("repos dispatch", repository("Contents", true)),
+        Command::Runs(Runs::List { .. }) => ("runs list", repository("Actions", false)),
+        Command::Runs(Runs::View { .. }) => ("runs view", repository("Actions", false)),
+        Command::Runs(Runs::Watch { .. }) => ("runs watch", repository("Actions", false)),
+        Command::Runs(Runs::Logs { .. }) => ("runs logs", repository("Actions", false)),
+        Command::Runs(Runs::JobLogs { .. }) => ("runs job-logs", repository("Actions", false)),
+        Command::Runs(Runs::Cancel { .. }) => ("runs cancel", repository("Actions", true)),
+        Command::Runs(Runs::Rerun { .. }) => ("runs rerun", repository("Actions", true)),
+        Command::Runs(Runs::Delete { .. }) => ("runs delete", repository("Actions", true)),
+        Command::Secrets(secrets) => return secrets_permission(secrets),
+        Command::Workflows(Workflows::List { .. }) => {
+            ("workflows list", repository("Actions", false))
+        }
+        Command::Workflows(Workflows::Usage { .. }) => {
+            ("workflows usage", repository("Actions", false))
+        }
+        Command::Workflows(Workflows::Show { .. }) => {
+            ("workflows show", repository("Contents", false))
+        }
+        Command::Workflows(Workflows::Dispatch { .. }) => {
+            ("workflows dispatch", repository("Actions", true))
+        }
+        Command::Workflows(Workflows::Enable { .. }) => {
+            ("workflows enable", repository("Actions", true))
+        }
+        Command::Workflows(Workflows::Disable { .. }) => {
+            ("workflows disable", repository("Actions", true))
+        }
+        // billing reads both actions and plan permissions while repos and api requests
+        // depend on what they're asked for
+        Command::Workflows(Workflows::Billing { .. })
+        | Command::Api(_)
+        | Command::Repos(_)
+        | Command::Whoami
+        | Command::Completions { .. } => return None,
+    };
+    Some(required)
+}
+
+/// Secrets of organizations and environments require their own permissions
+fn secrets_permission(secrets: &Secrets) -> Option<(&'static str, Permission)> {
+    let (command, owner, write) = match secrets {
+        Secrets::List { owner } => ("secrets list", owner, false),
+        Secrets::Exists { owner, .. } => ("secrets exists", owner, false),
+        Secrets::PublicKey { owner } => ("secrets public-key", owner, false),
+        Secrets::Create { owner, .. } => ("secrets create", owner, true),
+        Secrets::Import { owner, .. } => ("secrets import", owner, true),
+        Secrets::Delete { owner, .. } => ("secrets delete", owner, true),
+        Secrets::Copy { .. } => return Some(("secrets copy", repository("Secrets", true))),
+    };
+    let permission = match (&owner.org, &owner.environment) {
+        (Some(_), _) => Permission {
+            name: "Secrets",
+            organization: true,
+            write,
+        },
+        (None, Some(_)) => repository("Environments", write),
+        (None, None) => repository("Secrets", write),
+    };
+    Some((command, permission))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use structopt::StructOpt;
+
+    fn required_by(args: &[&str]) -> Option<String> {
+        let options =
+            crate::Options::from_iter_safe(std::iter::once("actions").chain(args.iter().copied()))
+                .unwrap();
+        required(&options.command)
+            .map(|(command, permission)| format!("{} requires the {}", command, permission))
+    }
+
+    #[test]
+    fn required_names_each_commands_permission() {
+        assert_eq!(
+            required_by(&["secrets", "list"]).as_deref(),
+            Some("secrets list requires the 'Secrets' repository permission with read access")
+        );
+        assert_eq!(
+            required_by(&["secrets", "delete", "--org", "acme", "TOKEN"]).as_deref(),
+            Some("secrets delete requires the 'Secrets' organization permission with read and write access")
+        );
+        assert_eq!(
+            required_by(&["secrets", "list", "-e", "production"]).as_deref(),
+            Some("secrets list requires the 'Environments' repository permission with read access")
+        );
+        assert_eq!(
+            required_by(&["runs", "cancel", "--run-id", "1"]).as_deref(),
+            Some("runs cancel requires the 'Actions' repository permission with read and write access")
+        );
+        assert_eq!(
+            required_by(&["repos", "dispatch", "--event-type", "deploy"]).as_deref(),
+            Some("repos dispatch requires the 'Contents' repository permission with read and write access")
+        );
+        assert_eq!(required_by(&["repos", "--org", "acme"]), None);
+        assert_eq!(required_by(&["api", "get", "/user"]), None);
+    }
+}
@@ -1,34 +1,290 @@
-use crate::{github::Requests, StringErr};
-use reqwest::Client;
-use std::{
-    env,
-    error::Error,
-    io::{stdout, Write},
+use crate::{
+    format::{Format, Table},
+    github::{ActionsError, Repo, RepositoryDispatch},
+    print_fields, print_json, print_json_line, Config, Output, StringErr,
 };
+use colored::Colorize;
+use futures::{future, stream, StreamExt, TryStreamExt};
+use serde_json::Value;
+use std::{cmp::Reverse, convert::Infallible, error::Error, fs, io, path::Path};
 use structopt::StructOpt;
-use tabwriter::TabWriter;
 
 /// 🌌 Discover repos using GitHub Actions (experimental)
 #[derive(StructOpt, Debug)]
 pub struct Repos {
-    /// GitHub repository in the form `owner/repo`
+    #[structopt(subcommand)]
+    pub command: Option<RepoCommand>,
+    /// GitHub organization. Required unless a subcommand is given
     #[structopt(short, long, env = "ACTIONS_ORG")]
-    org: String,
-}
-
-pub async fn repos(args: Repos) -> Result<(), Box<dyn Error>> {
-    let Repos { org } = args;
-    let client = Client::new();
-    let token = env::var("GITHUB_TOKEN")
-        .map_err(|_| StringErr("Please provide a GITHUB_TOKEN env variable".into()))?;
-    let requests = Requests { client, token };
-    let repos = requests.clone().repos(org).await;
-    let mut writer = TabWriter::new(stdout());
-    writeln!(writer, "Repo\tWorkflow Count")?;
-    for repo in repos {
-        writeln!(writer, "{}\t{}", repo.full_name, repo.workflows.len())?;
+    org: Option<String>,
+    /// List each repo's workflow paths under it
+    #[structopt(long)]
+    show_workflows: bool,
+    /// Only list repos with at least this many workflows
+    #[structopt(long)]
+    min_workflows: Option<usize>,
+    /// Only list repos with at most this many workflows. Repos are discovered by their
+    /// workflow files, so repos without any are never listed
+    #[structopt(long)]
+    max_workflows: Option<usize>,
+    /// Don't list archived repos. Each repo's details are then fetched to tell
+    #[structopt(long)]
+    exclude_archived: bool,
+    /// Don't list repos which are forks. Each repo's details are then fetched to tell
+    #[structopt(long)]
+    exclude_forks: bool,
+    /// Sort repos by 'count' of workflows, most first. Repos are listed by name by default
+    #[structopt(long, possible_values = &["count"])]
+    sort: Option<String>,
+    /// Format of output 'tab' (default), 'csv' or 'md', a markdown table
+    #[structopt(default_value = "tab", short, long, env = "ACTIONS_FORMAT")]
+    format: Format,
+}
+
+/// Commands acting on a single repository
+#[derive(StructOpt, Debug)]
+pub enum RepoCommand {
+    /// Trigger a repository_dispatch event, running the --repository's workflows listening for it
+    Dispatch {
+        /// Type of event, matched by the types of workflows' repository_dispatch triggers
+        #[structopt(long)]
+        event_type: String,
+        /// Json object made available to workflows as github.event.client_payload
+        #[structopt(long, default_value = "{}", parse(try_from_str = parse_payload))]
+        payload: Value,
+    },
+}
+
+/// Parses a json object, as client payloads must be objects
+fn parse_payload(payload: &str) -> Result<Value, String> {
+    match serde_json::from_str(payload) {
+        Ok(payload @ Value::Object(_)) => Ok(payload),
+        Ok(_) => Err(format!(
+            "{} is not a json object. try {{\"key\": \"value\"}} instead",
+            payload
+        )),
+        Err(err) => Err(format!("{} is not valid json: {}", payload, err)),
     }
-    writer.flush()?;
+}
+
+/// Reads a file of repositories in the form owner/repo, one per line
+pub(crate) fn read_repos_file(path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| StringErr(format!("{} could not be read: {}", path.display(), err)))?;
+    Ok(parse_repos(&contents)?)
+}
+
+/// Parses owner/repo lines, skipping blank lines and # comments
+fn parse_repos(contents: &str) -> Result<Vec<String>, StringErr> {
+    contents
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| match line.split_once('/') {
+            Some((owner, repo)) if !owner.is_empty() && !repo.is_empty() && !repo.contains('/') => {
+                Ok(line.to_string())
+            }
+            _ => Err(StringErr(format!(
+                "line {} is not a repository in the form owner/repo",
+                number
+            ))),
+        })
+        .collect()
+}
+
+/// Whether a repo is left out by --exclude-archived or --exclude-forks. Repos whose
+/// details weren't fetched are never left out
+fn excluded(
+    repo: &Repo,
+    exclude_archived: bool,
+    exclude_forks: bool,
+) -> bool {
+    (exclude_archived && repo.archived == Some(true)) || (exclude_forks && repo.fork == Some(true))
+}
+
+pub async fn repos(
+    args: Repos,
+    config: Config,
+) -> Result<(), Box<dyn Error>> {
+    let Repos {
+        command,
+        org,
+        show_workflows,
+        min_workflows,
+        max_workflows,
+        exclude_archived,
+        exclude_forks,
+        sort,
+        format,
+    } = args;
+    if let Some(RepoCommand::Dispatch {
+        event_type,
+        payload,
+    }) = command
+    {
+        let repository = config.repository()?;
+        let requests = config.requests()?;
+        requests
+            .repository_dispatch(
+                &repository,
+                &RepositoryDispatch {
+                    event_type: event_type.clone(),
+                    client_payload: payload,
+                },
+            )
+            .await?;
+        config.changed(format!(
+            "Dispatched {} to {}",
+            event_type.bold(),
+            repository
+        ));
+        return Ok(());
+    }
+    let org = org.ok_or_else(|| StringErr("Please provide an --org".into()))?;
+    let requests = config.requests()?;
+    let progress = config.progress(&format!("Discovering {} repos", org));
+    // every repo is needed to filter or sort them before the limit applies
+    let selective = min_workflows.is_some()
+        || max_workflows.is_some()
+        || exclude_archived
+        || exclude_forks
+        || sort.is_some();
+    let repos = requests
+        .clone()
+        .repos(
+            org,
+            if selective {
+                usize::MAX
+            } else {
+                config.limit()
+            },
+            &progress,
+        )
+        .await;
+    progress.finish_and_clear();
+    let (mut repos, incomplete) = repos?;
+    if incomplete {
+        eprintln!(
+            "{}",
+            "Code search returned incomplete results, so some repos may be missing".yellow()
+        );
+    }
+    repos.retain(|repo| {
+        min_workflows.is_none_or(|min| repo.workflows.len() >= min)
+            && max_workflows.is_none_or(|max| repo.workflows.len() <= max)
+    });
+    if exclude_archived || exclude_forks {
+        // code search doesn't tell archived repos or forks apart, so each repo's details are fetched
+        repos = stream::iter(repos)
+            .map(|mut repo| {
+                let requests = &requests;
+                async move {
+                    let details = requests.repo(&repo.full_name).await?;
+                    repo.archived = Some(details.archived);
+                    repo.fork = Some(details.fork);
+                    Ok::<_, ActionsError>(repo)
+                }
+            })
+            .buffered(config.concurrency)
+            .try_filter(|repo| future::ready(!excluded(repo, exclude_archived, exclude_forks)))
+            .try_collect()
+            .await?;
+    }
+    if sort.is_some() {
+        // stable, so repos with as many workflows stay in name order
+        repos.sort_by_key(|repo| Reverse(repo.workflows.len()));
+    }
+    repos.truncate(config.limit());
+    if let Some(fields) = &config.fields {
+        let repos = stream::iter(repos.into_iter().map(Ok::<_, Infallible>));
+        return print_fields(&config, fields, format, repos).await;
+    }
+    match config.output {
+        Output::Json => return print_json(&repos),
+        Output::JsonLines => return repos.iter().try_for_each(print_json_line),
+        Output::Text => (),
+    }
+    let mut table = Table::with_pager(format, config.pager()).quiet(config.quiet);
+    write_repos(&mut table, &repos, show_workflows)?;
+    table.flush()?;
 
     Ok(())
 }
+
+/// Writes repos along with their workflow counts, optionally listing each repo's
+/// workflow paths under it
+fn write_repos(
+    table: &mut Table,
+    repos: &[Repo],
+    show_workflows: bool,
+) -> io::Result<()> {
+    table.header(&[&"Repo", &"Workflow Count"])?;
+    for repo in repos {
+        table.row(&[&repo.full_name, &repo.workflows.len()])?;
+        if show_workflows {
+            for path in &repo.workflows {
+                table.row(&[&format!("  {}", path.dimmed()), &""])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_repos_lists_workflow_paths_when_asked() {
+        let repos = vec![Repo {
+            full_name: "acme/api".into(),
+            workflows: vec![".github/workflows/ci.yml".into()],
+            archived: None,
+            fork: None,
+        }];
+        let mut out = Vec::new();
+        let mut table = Table::with_writer(Format::Csv, &mut out);
+        write_repos(&mut table, &repos, true).unwrap();
+        drop(table);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Repo,Workflow Count\nacme/api,1\n  .github/workflows/ci.yml,\n"
+        );
+    }
+
+    #[test]
+    fn parse_payload_only_accepts_json_objects() {
+        assert_eq!(
+            parse_payload(r#"{"env": "prod"}"#),
+            Ok(serde_json::json!({"env": "prod"}))
+        );
+        assert!(parse_payload(r#"["prod"]"#).is_err());
+        assert!(parse_payload("env=prod").is_err());
+    }
+
+    #[test]
+    fn parse_repos_reads_one_repo_per_line() {
+        assert_eq!(
+            parse_repos("# fleet\nacme/api\n\n  acme/web  \n").unwrap(),
+            vec!["acme/api".to_string(), "acme/web".to_string()]
+        );
+        assert!(parse_repos("acme").is_err());
+        assert!(parse_repos("acme/api/extra").is_err());
+    }
+
+    #[test]
+    fn excluded_only_leaves_out_repos_asked_for() {
+        let repo = |archived, fork| Repo {
+            full_name: "acme/api".into(),
+            workflows: vec![".github/workflows/ci.yml".into()],
+            archived,
+            fork,
+        };
+        assert!(!excluded(&repo(Some(false), Some(false)), true, true));
+        assert!(excluded(&repo(Some(true), Some(false)), true, false));
+        assert!(!excluded(&repo(Some(true), Some(false)), false, true));
+        assert!(excluded(&repo(Some(false), Some(true)), false, true));
+        assert!(!excluded(&repo(None, None), true, true));
+    }
+}
@@ -1,144 +1,1082 @@
 use crate::{
-    github::{Requests, Workflow},
-    StringErr,
+    confirm,
+    format::{Format, Table},
+    github::{ActionsError, Job, Requests, Run, RunFilter},
+    print_json, print_json_line,
+    repos::read_repos_file,
+    workflows::{filtered_workflows, resolve_workflow},
+    Config, Failure, Output, StringErr,
 };
-use chrono::{offset::TimeZone, DateTime, Datelike, Utc};
-use colored::Colorize;
-use futures::{stream::Stream, StreamExt};
-use humantime::format_duration;
-use reqwest::Client;
+use chrono::{
+    format::{Item, StrftimeItems},
+    offset::TimeZone,
+    DateTime, Datelike, Local, SecondsFormat, Utc,
+};
+use colored::{ColoredString, Colorize};
+use futures::{
+    stream::{self, Stream},
+    StreamExt, TryStreamExt,
+};
+use humantime::{format_duration, parse_duration};
 use std::{
-    env,
+    cell::Cell,
+    cmp::Ordering,
+    collections::BTreeMap,
     error::Error,
-    io::{stdout, Write},
+    fmt,
+    fs::{self, File},
+    io::{self, stdout, Cursor, Write},
+    path::{Path, PathBuf},
     pin::Pin,
+    process::Command,
+    rc::Rc,
     str::FromStr,
+    time::{Duration, Instant},
 };
 use structopt::StructOpt;
-use tabwriter::TabWriter;
+use tokio::time::delay_for;
+use zip::ZipArchive;
 
-#[derive(Debug)]
-pub enum Format {
-    Tab,
-    Csv,
+/// 🏃 Get workflow run information
+#[derive(StructOpt, Debug)]
+pub enum Runs {
+    /// List runs for a given workflow, or for every workflow of the repository
+    List {
+        /// Workflow file name or name, i.e. ci.yml. Runs of every workflow are listed, newest
+        /// first, when omitted
+        #[structopt(short, long, env = "ACTIONS_WORKFLOW")]
+        workflow: Option<String>,
+        /// List all runs since date in yyyy-mm-dd format, today, yesterday, last-week or a
+        /// duration ago such as 24h, 7d or 2w. Defaults to the start of the month
+        #[structopt(short, long, env = "ACTIONS_SINCE")]
+        since: Option<String>,
+        /// List only runs created on or before date in yyyy-mm-dd format
+        #[structopt(short, long, env = "ACTIONS_UNTIL", parse(try_from_str = end_of_day))]
+        until: Option<DateTime<Utc>>,
+        /// Only list runs with this status. Lists runs of any status by default
+        #[structopt(long, possible_values = &["completed", "in_progress", "queued", "waiting"])]
+        status: Option<String>,
+        /// Only list runs for this branch
+        #[structopt(long, alias = "ref")]
+        branch: Option<String>,
+        /// Only list runs triggered by this event, i.e. push or pull_request
+        #[structopt(long)]
+        event: Option<String>,
+        /// Only list runs triggered by this user's login
+        #[structopt(long)]
+        actor: Option<String>,
+        /// Format of output 'tab' (default), 'csv' or 'md', a markdown table
+        #[structopt(default_value = "tab", short, long, env = "ACTIONS_FORMAT")]
+        format: Format,
+        /// List each run's jobs under it
+        #[structopt(long)]
+        with_jobs: bool,
+        /// Only print a summary of the listed runs
+        #[structopt(long, conflicts_with = "with-jobs")]
+        summary_only: bool,
+        /// Exit with status 2 when any listed run concluded with failure, cancelled or timed_out
+        #[structopt(long)]
+        fail_on_failure: bool,
+        /// Sort listed runs by 'created', 'updated' or 'duration'. Runs are listed as they
+        /// are fetched, newest first, by default
+        #[structopt(long, possible_values = &["created", "updated", "duration"])]
+        sort: Option<Sort>,
+        /// Order of sorted runs 'desc' (default) or 'asc'. Only applies with --sort
+        #[structopt(long, default_value = "desc", possible_values = &["asc", "desc"])]
+        order: Order,
+        /// Print the time each run was created in the local timezone rather than UTC
+        #[structopt(long)]
+        local_time: bool,
+        /// Print how long ago each run was created, i.e. 2 hours ago, rather than the time.
+        /// Csv output still lists the time
+        #[structopt(long, conflicts_with = "local-time")]
+        relative_time: bool,
+        /// Format of run and job durations 'human' (default), i.e. 3m 20s, or a whole number
+        /// of 'seconds' or 'ms'
+        #[structopt(long, default_value = "human", possible_values = &["human", "seconds", "ms"])]
+        duration_format: DurationFormat,
+        /// List runs grouped by branch, each under a line counting its successful and
+        /// failed runs. Runs are only listed once all of them are fetched
+        #[structopt(long)]
+        group_by_branch: bool,
+        /// File of repositories whose runs are all listed, in place of --repository, with one
+        /// owner/repo per line. Workflows are then prefixed with their repository
+        #[structopt(long, parse(from_os_str))]
+        repos_file: Option<PathBuf>,
+    },
+    /// Cancel one or more workflow runs
+    Cancel {
+        /// Id of run. May be provided multiple times
+        #[structopt(long, required = true)]
+        run_id: Vec<usize>,
+    },
+    /// Show the details of a workflow run
+    View {
+        /// Id of run
+        #[structopt(long)]
+        run_id: usize,
+        /// Open the run in a web browser instead
+        #[structopt(long)]
+        web: bool,
+    },
+    /// Wait for a workflow run to complete, printing its status as it changes.
+    /// Exits with status 2 unless the run succeeds
+    Watch {
+        /// Id of run
+        #[structopt(long)]
+        run_id: usize,
+        /// How often to check the run's status
+        #[structopt(long, default_value = "5s", parse(try_from_str = parse_duration))]
+        interval: Duration,
+        /// How long to wait for the run to complete before giving up
+        #[structopt(long, parse(try_from_str = parse_duration))]
+        timeout: Option<Duration>,
+    },
+    /// Download a workflow run's logs
+    Logs {
+        /// Id of run
+        #[structopt(long)]
+        run_id: usize,
+        /// File to save the logs archive to, or directory to extract it into with --extract.
+        /// Defaults to logs-<run-id>.zip or logs-<run-id>
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+        /// Extract the logs archive, preserving its per-job log file layout
+        #[structopt(long)]
+        extract: bool,
+    },
+    /// Print the plain text log of a single job of a run, i.e. one that failed
+    JobLogs {
+        /// Id of job, which ends the job urls listed by runs list --with-jobs
+        #[structopt(long)]
+        job_id: usize,
+        /// File to save the log to instead of printing it
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Re-run a workflow run
+    Rerun {
+        /// Id of run
+        #[structopt(long)]
+        run_id: usize,
+        /// Only re-run the jobs that failed
+        #[structopt(long)]
+        failed_only: bool,
+    },
+    /// Delete workflow runs along with their logs and artifacts
+    Delete {
+        /// Id of run. May be provided multiple times
+        #[structopt(long, required_unless = "workflow")]
+        run_id: Vec<usize>,
+        /// Workflow file name or name whose runs are deleted, in place of --run-id. Requires --older-than
+        #[structopt(short, long, conflicts_with = "run-id", requires = "older-than")]
+        workflow: Option<String>,
+        /// Only delete the workflow's runs created longer than this ago, i.e. 30days
+        #[structopt(long, parse(try_from_str = parse_duration), requires = "workflow")]
+        older_than: Option<Duration>,
+        /// Delete without asking for confirmation
+        #[structopt(short, long)]
+        yes: bool,
+    },
+}
+
+/// Collects up to `limit` runs, each along with the name of the workflow it's listed under,
+/// fetching each run's jobs when asked
+async fn collect_runs(
+    requests: Requests,
+    runs: impl Stream<Item = Result<(String, Run), ActionsError>>,
+    limit: usize,
+    concurrency: usize,
+    fetch_jobs: bool,
+) -> Result<Vec<(String, Run, Vec<Job>)>, ActionsError> {
+    runs.take(limit)
+        .map_ok(|(name, run)| {
+            let requests = requests.clone();
+            async move {
+                let jobs = if fetch_jobs {
+                    requests.jobs(&run.jobs_url).try_collect().await?
+                } else {
+                    Vec::new()
+                };
+                Ok((name, run, jobs))
+            }
+        })
+        .try_buffered(concurrency)
+        .try_collect()
+        .await
 }
 
-impl Default for Format {
-    fn default() -> Self {
-        Format::Tab
+/// Extracts a logs archive into a directory, returning the number of files extracted
+fn extract_logs(
+    archive: &[u8],
+    dir: &Path,
+) -> Result<usize, Box<dyn Error>> {
+    let mut archive = ZipArchive::new(Cursor::new(archive))?;
+    let mut extracted = 0;
+    for idx in 0..archive.len() {
+        let mut entry = archive.by_index(idx)?;
+        let path = match entry.enclosed_name() {
+            Some(name) => dir.join(name),
+            None => continue,
+        };
+        if entry.is_dir() {
+            fs::create_dir_all(&path)?;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut entry, &mut File::create(&path)?)?;
+        extracted += 1;
+    }
+    Ok(extracted)
+}
+
+fn colored_conclusion(conclusion: &str) -> ColoredString {
+    match conclusion {
+        "failure" => "failure".red(),
+        "success" => "success".green(),
+        other => other.dimmed(),
     }
 }
 
-impl FromStr for Format {
+/// Field to sort listed runs by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sort {
+    Created,
+    Updated,
+    Duration,
+}
+
+impl FromStr for Sort {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "csv" => Ok(Format::Csv),
-            "tab" => Ok(Format::Tab),
+            "created" => Ok(Sort::Created),
+            "updated" => Ok(Sort::Updated),
+            "duration" => Ok(Sort::Duration),
             other => Err(format!(
-                "{} is not a supported format. try 'csv' or 'tab' instead",
+                "{} is not a supported sort. try 'created', 'updated' or 'duration' instead",
                 other
             )),
         }
     }
 }
 
-/// 🏃 Get workflow run information
-#[derive(StructOpt, Debug)]
-pub enum Runs {
-    /// List runs for a given workflow
-    List {
-        /// GitHub repository in the form owner/repo
-        #[structopt(short, long, env = "ACTIONS_REPOSITORY")]
-        repository: String,
-        /// Workflow name
-        #[structopt(short, long, env = "ACTIONS_WORKFLOW")]
-        workflow: String,
-        /// List all runs since date in yyyy-mm-dd format
-        #[structopt(short, long, env = "ACTIONS_SINCE")]
-        since: Option<String>,
-        /// Format of output 'tab' (default) or 'csv'
-        #[structopt(default_value = "tab", short, long, env = "ACTIONS_FORMAT")]
-        format: Format,
-    },
+impl Sort {
+    fn compare(
+        self,
+        a: &Run,
+        b: &Run,
+        order: Order,
+    ) -> Ordering {
+        let ordering = match self {
+            Sort::Created => a.created_at.cmp(&b.created_at),
+            Sort::Updated => a.updated_at.cmp(&b.updated_at),
+            Sort::Duration => a.duration().cmp(&b.duration()),
+        };
+        match order {
+            Order::Asc => ordering,
+            Order::Desc => ordering.reverse(),
+        }
+    }
 }
 
-fn filtered_workflows(
-    workflow: Option<String>,
-    workflows: impl Stream<Item = Workflow>,
-) -> impl Stream<Item = Workflow> {
-    workflows.filter(move |flow| {
-        let matched = workflow.as_ref().map_or(true, |name| {
-            flow.name.to_lowercase().contains(&name.to_lowercase())
-        });
-        async move { matched }
-    })
+/// How listed durations are written
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DurationFormat {
+    Human,
+    Seconds,
+    Millis,
+}
+
+impl FromStr for DurationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(DurationFormat::Human),
+            "seconds" => Ok(DurationFormat::Seconds),
+            "ms" => Ok(DurationFormat::Millis),
+            other => Err(format!(
+                "{} is not a supported duration format. try 'human', 'seconds' or 'ms' instead",
+                other
+            )),
+        }
+    }
+}
+
+impl DurationFormat {
+    fn format(
+        self,
+        duration: Duration,
+    ) -> String {
+        match self {
+            DurationFormat::Human => format_duration(duration).to_string(),
+            DurationFormat::Seconds => duration.as_secs().to_string(),
+            DurationFormat::Millis => duration.as_millis().to_string(),
+        }
+    }
+}
+
+/// Direction of sorted runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl FromStr for Order {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "asc" => Ok(Order::Asc),
+            "desc" => Ok(Order::Desc),
+            other => Err(format!(
+                "{} is not a supported order. try 'asc' or 'desc' instead",
+                other
+            )),
+        }
+    }
+}
+
+/// Opens a url with the platform's default browser
+fn open_in_browser(url: &str) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = command.arg(url).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{} could not be opened in a browser",
+            url
+        )))
+    }
+}
+
+/// Whether a run's conclusion counts as a failure for `--fail-on-failure`
+fn failed_conclusion(conclusion: Option<&str>) -> bool {
+    matches!(conclusion, Some("failure" | "cancelled" | "timed_out"))
 }
 
-fn date_or_first_of_the_month(timestamp: Option<impl AsRef<str>>) -> DateTime<Utc> {
+/// How listed runs, along with their jobs, are written as table rows
+struct RunRows {
+    time_format: Option<String>,
+    local_time: bool,
+    /// Time that creation times are written relative to, when they are
+    relative_to: Option<DateTime<Utc>>,
+    duration_format: DurationFormat,
+    with_jobs: bool,
+}
+
+impl RunRows {
+    fn write(
+        &self,
+        table: &mut Table,
+        workflow: &str,
+        run: &Run,
+        jobs: &[Job],
+    ) -> io::Result<()> {
+        let created = match (table.format(), self.relative_to) {
+            (Format::Csv, _) => format_time(run.created_at, self.time_format.as_deref(), false),
+            (_, Some(now)) => relative_time(run.created_at, now),
+            _ => format_time(run.created_at, self.time_format.as_deref(), self.local_time),
+        };
+        table.row(&[
+            &workflow,
+            &run.id,
+            &created,
+            &colored_conclusion(run.conclusion.as_deref().unwrap_or_default()),
+            &self.duration_format.format(run.duration()),
+            &run.html_url.dimmed(),
+        ])?;
+        if let (true, Some(actor)) = (self.with_jobs, &run.actor) {
+            table.row(&[
+                &"",
+                &format!("  by {}", actor.login).dimmed(),
+                &"",
+                &"",
+                &"",
+                &"",
+            ])?;
+        }
+        for job in jobs {
+            table.row(&[
+                &"",
+                &format!("  {}", job.name),
+                &"",
+                &colored_conclusion(job.conclusion.as_deref().unwrap_or(&job.status)),
+                &job.duration().map_or_else(
+                    || "-".to_string(),
+                    |duration| self.duration_format.format(duration),
+                ),
+                &job.html_url.dimmed(),
+            ])?;
+        }
+        Ok(())
+    }
+}
+
+/// Aggregate statistics of listed runs
+#[derive(Default)]
+struct Summary {
+    conclusions: BTreeMap<String, usize>,
+    durations: Vec<Duration>,
+}
+
+impl Summary {
+    fn add(
+        &mut self,
+        run: &Run,
+    ) {
+        *self
+            .conclusions
+            .entry(run.conclusion.clone().unwrap_or_else(|| run.status.clone()))
+            .or_default() += 1;
+        self.durations.push(run.duration());
+    }
+
+    fn total(&self) -> usize {
+        self.durations.len()
+    }
+
+    /// Mean run duration, rounded to the second
+    fn average(&self) -> Duration {
+        let total = self.durations.iter().sum::<Duration>();
+        let average = total.checked_div(self.total() as u32).unwrap_or_default();
+        Duration::from_secs(average.as_secs_f64().round() as u64)
+    }
+
+    /// Duration that 95% of runs completed within, using the nearest rank
+    fn p95(&self) -> Duration {
+        let mut durations = self.durations.clone();
+        durations.sort();
+        let rank = (durations.len() as f64 * 0.95).ceil() as usize;
+        durations
+            .get(rank.saturating_sub(1))
+            .map(|p95| Duration::from_secs(p95.as_secs()))
+            .unwrap_or_default()
+    }
+
+    /// Writes the summary below a table's rows so that both are paged together
+    fn print(
+        &self,
+        table: &mut Table,
+        duration_format: DurationFormat,
+    ) -> io::Result<()> {
+        table.line(&format!("Total runs {}", self.total().to_string().bold()))?;
+        if !self.conclusions.is_empty() {
+            table.line(
+                &self
+                    .conclusions
+                    .iter()
+                    .map(|(conclusion, count)| {
+                        format!("{} {}", colored_conclusion(conclusion), count)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )?;
+        }
+        table.line(&format!(
+            "Average duration {}",
+            duration_format.format(self.average())
+        ))?;
+        table.line(&format!(
+            "p95 duration {}",
+            duration_format.format(self.p95())
+        ))
+    }
+}
+
+/// Number of successful and failed runs of each branch
+fn branch_counts<'a>(runs: impl IntoIterator<Item = &'a Run>) -> BTreeMap<String, (usize, usize)> {
+    let mut counts = BTreeMap::<String, (usize, usize)>::new();
+    for run in runs {
+        let (succeeded, failed) = counts.entry(run.head_branch.clone()).or_default();
+        if run.conclusion.as_deref() == Some("success") {
+            *succeeded += 1;
+        } else if failed_conclusion(run.conclusion.as_deref()) {
+            *failed += 1;
+        }
+    }
+    counts
+}
+
+fn date_or_first_of_the_month(
+    timestamp: Option<impl AsRef<str>>,
+    now: DateTime<Utc>,
+) -> DateTime<Utc> {
     timestamp
-        .and_then(|ts| {
-            chrono::NaiveDate::parse_from_str(ts.as_ref(), "%Y-%m-%d")
-                .ok()
-                .map(|fixed| {
-                    let then = fixed.and_hms(0, 0, 0);
-                    Utc.ymd(then.year(), then.month(), then.day())
-                        .and_hms(0, 0, 0)
-                })
-        })
-        .unwrap_or_else(|| {
-            let now = Utc::now().naive_utc();
-            Utc.ymd(now.year(), now.month(), 1).and_hms(0, 0, 0)
-        })
+        .and_then(|ts| parse_since(ts.as_ref(), now))
+        .unwrap_or_else(|| Utc.ymd(now.year(), now.month(), 1).and_hms(0, 0, 0))
+}
+
+/// Parses a yyyy-mm-dd date, one of the keywords `today`, `yesterday` or `last-week`,
+/// or a duration before now such as `24h`, `7d` or `2w`
+fn parse_since(
+    since: &str,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        return Some(Utc.from_utc_datetime(&date.and_hms(0, 0, 0)));
+    }
+    let today = now.date().and_hms(0, 0, 0);
+    match since {
+        "today" => Some(today),
+        "yesterday" => Some(today - chrono::Duration::days(1)),
+        "last-week" => Some(now - chrono::Duration::weeks(1)),
+        duration => Some(now - chrono::Duration::from_std(parse_duration(duration).ok()?).ok()?),
+    }
+}
+
+/// Parses a yyyy-mm-dd date into the start of the following day, so that a range
+/// ending on that date includes runs created at any time during it
+fn end_of_day(date: &str) -> Result<DateTime<Utc>, String> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|date| Utc.from_utc_datetime(&date.succ().and_hms(0, 0, 0)))
+        .map_err(|_| format!("{} is not a valid date. try yyyy-mm-dd instead", date))
 }
 
-pub async fn runs(args: Runs) -> Result<(), Box<dyn Error>> {
+pub(crate) fn parse_time_format(format: &str) -> Result<String, String> {
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return Err(format!(
+            "{} is not a valid time format. try a strftime format such as '%Y-%m-%d %H:%M' instead",
+            format
+        ));
+    }
+    Ok(format.into())
+}
+
+/// Formats how long before `now` a time was, i.e. `3 hours ago`, in its largest whole unit
+fn relative_time(
+    at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> String {
+    let ago = now - at;
+    let (count, unit) = if ago.num_days() > 0 {
+        (ago.num_days(), "day")
+    } else if ago.num_hours() > 0 {
+        (ago.num_hours(), "hour")
+    } else if ago.num_minutes() > 0 {
+        (ago.num_minutes(), "minute")
+    } else {
+        return "just now".into();
+    };
+    format!(
+        "{} {}{} ago",
+        count,
+        unit,
+        if count == 1 { "" } else { "s" }
+    )
+}
+
+/// Formats a time using a strftime format, or as RFC 3339 when none is given
+pub(crate) fn format_time(
+    at: DateTime<Utc>,
+    format: Option<&str>,
+    local: bool,
+) -> String {
+    fn render<Tz: TimeZone>(
+        at: DateTime<Tz>,
+        format: Option<&str>,
+    ) -> String
+    where
+        Tz::Offset: fmt::Display,
+    {
+        match format {
+            Some(format) => at.format(format).to_string(),
+            None => at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        }
+    }
+    if local {
+        render(at.with_timezone(&Local), format)
+    } else {
+        render(at, format)
+    }
+}
+
+pub async fn runs(
+    args: Runs,
+    config: Config,
+) -> Result<(), Box<dyn Error>> {
     match args {
         Runs::List {
-            repository,
             workflow,
             since,
-            ..
+            until,
+            status,
+            branch,
+            event,
+            actor,
+            format,
+            with_jobs,
+            summary_only,
+            fail_on_failure,
+            sort,
+            order,
+            local_time,
+            relative_time,
+            duration_format,
+            group_by_branch,
+            repos_file,
         } => {
-            let since = date_or_first_of_the_month(since);
-            let mut writer = TabWriter::new(stdout());
-
-            let client = Client::new();
-            let token = env::var("GITHUB_TOKEN")
-                .map_err(|_| StringErr("Please provide a GITHUB_TOKEN env variable".into()))?;
-            let requests = Requests { client, token };
-            let mut workflows = filtered_workflows(
-                Some(workflow),
-                requests.clone().workflows(repository.clone()),
-            )
-            .boxed();
-            while let Some(workflow) = Pin::new(&mut workflows).next().await {
-                let mut runs = requests
-                    .clone()
-                    .runs(repository.clone(), workflow.id.to_string(), since)
-                    .boxed();
-                Pin::new(&mut runs)
-                    .for_each_concurrent(Some(20), |run| {
-                        let workflow = workflow.clone();
-                        async move {
+            let with_repository = repos_file.is_some();
+            let repositories = match repos_file {
+                Some(repos_file) => read_repos_file(&repos_file)?,
+                None => vec![config.repository()?],
+            };
+            let filter = RunFilter {
+                since: date_or_first_of_the_month(since, Utc::now()),
+                until,
+                status,
+                branch,
+                event,
+                actor,
+            };
+            let mut table = Table::with_pager(format, config.pager()).quiet(config.quiet);
+
+            let requests = config.requests()?;
+            let limit = config.limit();
+            let concurrency = config.concurrency;
+            let fetch_jobs = with_jobs && config.output == Output::Text;
+            let label = move |repository: &str, workflow: &str| {
+                if with_repository {
+                    format!("{} {}", repository, workflow)
+                } else {
+                    workflow.to_string()
+                }
+            };
+            let (requests, filter) = (&requests, &filter);
+            // runs of several workflows are fetched at once but listed in workflow order
+            let mut workflow_runs = match workflow {
+                Some(workflow) => stream::iter(repositories)
+                    .map(move |repository| {
+                        filtered_workflows(
+                            Some(workflow.clone()),
+                            false,
+                            requests.clone().workflows(repository.clone()),
+                        )
+                        .map_ok(move |workflow| (repository.clone(), workflow))
+                    })
+                    .flatten()
+                    .map_ok(move |(repository, workflow)| {
+                        let name = label(&repository, &workflow.name);
+                        let runs = requests
+                            .clone()
+                            .runs(repository, workflow.id.to_string(), filter.clone())
+                            .map_ok(move |run| (name.clone(), run));
+                        collect_runs(requests.clone(), runs, limit, concurrency, fetch_jobs)
+                    })
+                    .try_buffered(concurrency)
+                    .boxed(),
+                // each repository's runs of every workflow are listed together, newest first
+                None => stream::iter(repositories)
+                    .map(move |repository| {
+                        let runs = requests
+                            .clone()
+                            .all_runs(repository.clone(), filter.clone())
+                            .map_ok(move |run| (label(&repository, &run.workflow_name()), run));
+                        Ok(collect_runs(
+                            requests.clone(),
+                            runs,
+                            limit,
+                            concurrency,
+                            fetch_jobs,
+                        ))
+                    })
+                    .try_buffered(concurrency)
+                    .boxed(),
+            };
+            let mut listed = Vec::new();
+            let mut remaining = limit;
+            let mut summary = Summary::default();
+            let mut failed = 0;
+            let fields = config.fields.as_ref();
+            let rows = RunRows {
+                time_format: config.date_format.clone(),
+                local_time,
+                relative_to: if relative_time {
+                    Some(Utc::now())
+                } else {
+                    None
+                },
+                duration_format,
+                with_jobs,
+            };
+            let columns: [&dyn fmt::Display; 6] = [
+                &"Workflow",
+                &"Run",
+                &"Created",
+                &"Conclusion",
+                &"Duration",
+                &"Url",
+            ];
+            // each branch's runs are listed under their own columns
+            let branch_lines = group_by_branch
+                && config.output == Output::Text
+                && fields.is_none()
+                && !summary_only
+                && !config.quiet
+                && table.format() == Format::Tab;
+            if let (Output::Text, Some(fields)) = (config.output, fields) {
+                table.header_cells(fields.names())?;
+            } else if config.output == Output::Text && !summary_only && !branch_lines {
+                table.header(&columns)?;
+            }
+            let mut list = |table: &mut Table,
+                            workflow: &str,
+                            run: Run,
+                            jobs: Vec<Job>|
+             -> Result<(), Box<dyn Error>> {
+                if failed_conclusion(run.conclusion.as_deref()) {
+                    failed += 1;
+                }
+                if let Some(fields) = fields {
+                    match config.output {
+                        Output::Json => listed.push(fields.object(&run)?),
+                        Output::JsonLines => print_json_line(&fields.object(&run)?)?,
+                        Output::Text => table.cells(&fields.cells(&run)?)?,
+                    }
+                    return Ok(());
+                }
+                match config.output {
+                    Output::Json => {
+                        listed.push(serde_json::to_value(&run)?);
+                        return Ok(());
+                    }
+                    Output::JsonLines => return print_json_line(&run),
+                    Output::Text => (),
+                }
+                summary.add(&run);
+                if summary_only {
+                    return Ok(());
+                }
+                rows.write(table, workflow, &run, &jobs)?;
+                Ok(())
+            };
+            // runs are streamed as they arrive unless they need to be sorted or grouped first
+            let mut sorted = Vec::new();
+            while let Some(runs) = Pin::new(&mut workflow_runs).try_next().await? {
+                for (name, run, jobs) in runs.into_iter().take(remaining) {
+                    remaining -= 1;
+                    if sort.is_some() || group_by_branch {
+                        sorted.push((name, run, jobs));
+                    } else {
+                        list(&mut table, &name, run, jobs)?;
+                    }
+                }
+                if remaining == 0 {
+                    break;
+                }
+            }
+            if let Some(sort) = sort {
+                sorted.sort_by(|(_, a, _), (_, b, _)| sort.compare(a, b, order));
+            }
+            if group_by_branch {
+                // stable, so each branch's runs stay in the order they were listed in
+                sorted.sort_by(|(_, a, _), (_, b, _)| a.head_branch.cmp(&b.head_branch));
+            }
+            let counts = branch_counts(sorted.iter().map(|(_, run, _)| run));
+            let mut branch = None;
+            for (workflow, run, jobs) in sorted {
+                if branch_lines && branch.as_ref() != Some(&run.head_branch) {
+                    let (succeeded, failed) = counts[&run.head_branch];
+                    if branch.is_some() {
+                        table.line(&"")?;
+                    }
+                    table.line(&format!(
+                        "{} {} {}, {} {}",
+                        run.head_branch.bold(),
+                        succeeded,
+                        "success".green(),
+                        failed,
+                        "failure".red()
+                    ))?;
+                    table.header(&columns)?;
+                    branch = Some(run.head_branch.clone());
+                }
+                list(&mut table, &workflow, run, jobs)?;
+            }
+            if config.output == Output::Json {
+                print_json(&listed)?;
+            }
+            // --summary-only asks for nothing but the summary, so it's printed even when quiet
+            if config.output == Output::Text
+                && fields.is_none()
+                && (summary_only || (table.format() == Format::Tab && !config.quiet))
+            {
+                if !summary_only {
+                    table.line(&"")?;
+                }
+                summary.print(&mut table, duration_format)?;
+            }
+            table.flush()?;
+            // waits on any pager before failures are reported
+            drop(table);
+            if fail_on_failure && failed > 0 {
+                return Err(Failure(format!("{} listed run(s) did not succeed", failed)).into());
+            }
+        }
+        Runs::Cancel { run_id } => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let failures = Rc::new(Cell::new(0));
+            stream::iter(run_id)
+                .for_each_concurrent(Some(config.concurrency), |run_id| {
+                    let requests = requests.clone();
+                    let repository = repository.clone();
+                    let failures = failures.clone();
+                    let config = &config;
+                    async move {
+                        let cancelled = match requests.run(&repository, run_id).await {
+                            Ok(run) => requests.cancel(&run).await,
+                            Err(err) => Err(err),
+                        };
+                        match cancelled {
+                            Ok(()) => {
+                                config.changed(format!("Run {} is {}", run_id, "cancelled".green()))
+                            }
+                            Err(err) => {
+                                failures.set(failures.get() + 1);
+                                println!(
+                                    "{}",
+                                    format!("Run {} could not be cancelled: {}", run_id, err).red()
+                                )
+                            }
+                        }
+                    }
+                })
+                .await;
+            if failures.get() > 0 {
+                return Err(
+                    StringErr(format!("{} run(s) could not be cancelled", failures.get())).into(),
+                );
+            }
+        }
+        Runs::View { run_id, web } => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let run = requests.run(&repository, run_id).await?;
+            if web {
+                println!("Opening {}", run.html_url);
+                return Ok(open_in_browser(&run.html_url)?);
+            }
+            match config.output {
+                Output::Json => {
+                    println!("{}", serde_json::to_string_pretty(&run)?);
+                    return Ok(());
+                }
+                Output::JsonLines => return print_json_line(&run),
+                Output::Text => (),
+            }
+            println!("Run {}", run.id.to_string().bold());
+            let mut table = Table::new(Format::Tab);
+            table.row(&[&"branch", &run.head_branch])?;
+            table.row(&[&"event", &run.event])?;
+            if let Some(actor) = &run.actor {
+                table.row(&[&"actor", &actor.login])?;
+            }
+            table.row(&[&"status", &run.status])?;
+            table.row(&[
+                &"conclusion",
+                &colored_conclusion(run.conclusion.as_deref().unwrap_or("-")),
+            ])?;
+            let date_format = config.date_format.as_deref();
+            table.row(&[&"created", &format_time(run.created_at, date_format, false)])?;
+            table.row(&[&"updated", &format_time(run.updated_at, date_format, false)])?;
+            table.row(&[&"duration", &format_duration(run.duration())])?;
+            table.row(&[&"url", &run.html_url])?;
+            table.row(&[&"jobs", &run.jobs_url.dimmed()])?;
+            table.row(&[&"logs", &run.logs_url.dimmed()])?;
+            table.row(&[&"artifacts", &run.artifacts_url.dimmed()])?;
+            table.flush()?;
+        }
+        Runs::Watch {
+            run_id,
+            interval,
+            timeout,
+        } => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let started = Instant::now();
+            let mut status = None;
+            loop {
+                let run = requests.run(&repository, run_id).await?;
+                if status.as_ref() != Some(&run.status) {
+                    println!("Run {} is {}", run_id, run.status.bold());
+                    status = Some(run.status.clone());
+                }
+                if run.status == "completed" {
+                    let conclusion = run.conclusion.unwrap_or_default();
+                    println!(
+                        "Run {} concluded with {} {}",
+                        run_id,
+                        colored_conclusion(&conclusion),
+                        run.html_url.dimmed()
+                    );
+                    if conclusion != "success" {
+                        return Err(Failure(format!(
+                            "Run {} did not succeed: {}",
+                            run_id, conclusion
+                        ))
+                        .into());
+                    }
+                    break;
+                }
+                if let Some(timeout) = timeout.filter(|timeout| started.elapsed() >= *timeout) {
+                    return Err(StringErr(format!(
+                        "Run {} did not complete within {}",
+                        run_id,
+                        format_duration(timeout)
+                    ))
+                    .into());
+                }
+                delay_for(interval).await;
+            }
+        }
+        Runs::Logs {
+            run_id,
+            output,
+            extract,
+        } => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let logs = requests.run_logs(&repository, run_id).await?;
+            if extract {
+                let dir = output.unwrap_or_else(|| PathBuf::from(format!("logs-{}", run_id)));
+                let files = extract_logs(&logs, &dir)?;
+                println!("Extracted {} log files to {}", files, dir.display());
+            } else {
+                let file = output.unwrap_or_else(|| PathBuf::from(format!("logs-{}.zip", run_id)));
+                fs::write(&file, logs)?;
+                println!("Logs saved to {}", file.display());
+            }
+        }
+        Runs::JobLogs { job_id, output } => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let logs = requests.job_logs(&repository, job_id).await?;
+            match output {
+                Some(file) => {
+                    fs::write(&file, logs)?;
+                    println!("Log saved to {}", file.display());
+                }
+                None => stdout().write_all(&logs)?,
+            }
+        }
+        Runs::Rerun {
+            run_id,
+            failed_only,
+        } => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let html_url = requests.rerun(repository, run_id, failed_only).await?;
+            config.changed(format!("Run {} is re-running", run_id));
+            if let Some(html_url) = html_url {
+                println!("{}", html_url.dimmed());
+            }
+        }
+        Runs::Delete {
+            run_id,
+            workflow,
+            older_than,
+            yes,
+        } => {
+            let dry_run = config.dry_run;
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let run_ids = match (workflow, older_than) {
+                (Some(workflow), Some(older_than)) => {
+                    let workflow = resolve_workflow(&requests, &repository, &workflow).await?;
+                    let runs = requests
+                        .clone()
+                        .runs(
+                            repository.clone(),
+                            workflow.id.to_string(),
+                            RunFilter {
+                                since: Utc.timestamp(0, 0),
+                                until: Some(Utc::now() - chrono::Duration::from_std(older_than)?),
+                                status: None,
+                                branch: None,
+                                event: None,
+                                actor: None,
+                            },
+                        )
+                        .try_collect::<Vec<_>>()
+                        .await?;
+                    if dry_run {
+                        for run in &runs {
                             println!(
-                                "{} {} {} {} {}",
-                                workflow.name,
+                                "Would delete run {} created {} {}",
                                 run.id,
-                                match &run.conclusion.clone().unwrap_or_default()[..] {
-                                    "failure" => "failure".red(),
-                                    "success" => "success".green(),
-                                    other => other.dimmed(),
-                                },
-                                format_duration(run.duration()),
+                                format_time(run.created_at, config.date_format.as_deref(), false),
                                 run.html_url.dimmed()
-                            )
+                            );
                         }
-                    })
-                    .await;
+                    }
+                    runs.into_iter().map(|run| run.id).collect()
+                }
+                _ => {
+                    if dry_run {
+                        for run_id in &run_id {
+                            println!("Would delete run {}", run_id);
+                        }
+                    }
+                    run_id
+                }
+            };
+            if dry_run {
+                println!("{} run(s) would be deleted", run_ids.len());
+                return Ok(());
+            }
+            if run_ids.is_empty() {
+                println!("No runs to delete");
+                return Ok(());
+            }
+            if !yes
+                && !confirm(&format!(
+                    "Delete {} run(s) of {}?",
+                    run_ids.len(),
+                    repository
+                ))?
+            {
+                return Ok(());
+            }
+            let failures = Rc::new(Cell::new(0));
+            stream::iter(run_ids)
+                .for_each_concurrent(Some(config.concurrency), |run_id| {
+                    let requests = requests.clone();
+                    let repository = repository.clone();
+                    let failures = failures.clone();
+                    async move {
+                        match requests.delete_run(repository, run_id).await {
+                            Ok(()) => println!("Run {} is {}", run_id, "deleted".green()),
+                            Err(err) => {
+                                failures.set(failures.get() + 1);
+                                println!(
+                                    "{}",
+                                    format!("Run {} could not be deleted: {}", run_id, err).red()
+                                )
+                            }
+                        }
+                    }
+                })
+                .await;
+            if failures.get() > 0 {
+                return Err(
+                    StringErr(format!("{} run(s) could not be deleted", failures.get())).into(),
+                );
             }
-            writer.flush()?;
         }
     }
     Ok(())
@@ -147,43 +1085,273 @@ pub async fn runs(args: Runs) -> Result<(), Box<dyn Error>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use futures::stream;
-    use futures_await_test::async_test;
+    use crate::github::User;
+    use std::io::Write;
+
+    #[test]
+    fn list_parses_without_a_sort() {
+        assert!(Runs::from_iter_safe(&["runs", "list", "-w", "ci"]).is_ok());
+    }
 
-    #[async_test]
-    async fn filtered_workflows_filters_workflows_by_name() {
+    #[test]
+    fn format_time_defaults_to_rfc3339() {
+        let at = Utc.ymd(2020, 3, 12).and_hms(15, 30, 0);
+        assert_eq!(format_time(at, None, false), "2020-03-12T15:30:00Z");
         assert_eq!(
-            filtered_workflows(
-                Some("CI".into()),
-                stream::iter(vec![
-                    Workflow {
-                        id: 1,
-                        name: "ci test".into(),
-                        state: "completed".into(),
-                        path: ".github/workflows".into()
-                    },
-                    Workflow {
-                        id: 2,
-                        name: "test".into(),
-                        state: "completed".into(),
-                        path: ".github/workflows".into()
-                    }
-                ])
-            )
-            .collect::<Vec<_>>()
-            .await,
-            vec![Workflow {
-                id: 1,
-                name: "ci test".into(),
-                state: "completed".into(),
-                path: ".github/workflows".into()
-            }]
+            format_time(at, Some("%Y-%m-%d %H:%M"), false),
+            "2020-03-12 15:30"
+        );
+        assert!(parse_time_format("%Y-%m-%d").is_ok());
+        assert!(parse_time_format("%Q").is_err());
+    }
+
+    #[test]
+    fn relative_time_uses_the_largest_whole_unit() {
+        let now = Utc.ymd(2020, 3, 12).and_hms(15, 30, 0);
+        assert_eq!(relative_time(now, now), "just now");
+        assert_eq!(
+            relative_time(now + chrono::Duration::minutes(5), now),
+            "just now"
+        );
+        assert_eq!(
+            relative_time(now - chrono::Duration::seconds(90), now),
+            "1 minute ago"
+        );
+        assert_eq!(
+            relative_time(now - chrono::Duration::minutes(150), now),
+            "2 hours ago"
+        );
+        assert_eq!(
+            relative_time(now - chrono::Duration::days(3), now),
+            "3 days ago"
         );
     }
 
     #[test]
     fn date_or_first_of_the_month_parses_dates() {
-        let since = date_or_first_of_the_month(Some("2020-03-12"));
+        let since = date_or_first_of_the_month(Some("2020-03-12"), Utc::now());
         assert_eq!(since, Utc.ymd(2020, 3, 12).and_hms(0, 0, 0))
     }
+
+    #[test]
+    fn date_or_first_of_the_month_parses_relative_expressions() {
+        let now = Utc.ymd(2020, 3, 12).and_hms(15, 30, 0);
+        assert_eq!(
+            date_or_first_of_the_month(Some("7d"), now),
+            Utc.ymd(2020, 3, 5).and_hms(15, 30, 0)
+        );
+        assert_eq!(
+            date_or_first_of_the_month(Some("24h"), now),
+            Utc.ymd(2020, 3, 11).and_hms(15, 30, 0)
+        );
+        assert_eq!(
+            date_or_first_of_the_month(Some("yesterday"), now),
+            Utc.ymd(2020, 3, 11).and_hms(0, 0, 0)
+        );
+        assert_eq!(
+            date_or_first_of_the_month(Some("last-week"), now),
+            Utc.ymd(2020, 3, 5).and_hms(15, 30, 0)
+        );
+        assert_eq!(
+            date_or_first_of_the_month(Some("whenever"), now),
+            Utc.ymd(2020, 3, 1).and_hms(0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn end_of_day_includes_the_whole_day() {
+        assert_eq!(
+            end_of_day("2020-02-29"),
+            Ok(Utc.ymd(2020, 3, 1).and_hms(0, 0, 0))
+        );
+        assert!(end_of_day("2020-02-30").is_err());
+    }
+
+    #[test]
+    fn extract_logs_preserves_job_layout() -> Result<(), Box<dyn Error>> {
+        let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        archive.start_file("build/1_Set up job.txt", Default::default())?;
+        archive.write_all(b"setting up")?;
+        archive.start_file("1_build.txt", Default::default())?;
+        archive.write_all(b"building")?;
+        let archive = archive.finish()?.into_inner();
+
+        let dir = std::env::temp_dir().join(format!("actions-logs-{}", std::process::id()));
+        assert_eq!(extract_logs(&archive, &dir)?, 2);
+        assert_eq!(
+            fs::read_to_string(dir.join("build").join("1_Set up job.txt"))?,
+            "setting up"
+        );
+        assert_eq!(fs::read_to_string(dir.join("1_build.txt"))?, "building");
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn failed_conclusion_counts_cancelled_and_timed_out_runs() {
+        assert!(failed_conclusion(Some("failure")));
+        assert!(failed_conclusion(Some("cancelled")));
+        assert!(failed_conclusion(Some("timed_out")));
+        assert!(!failed_conclusion(Some("success")));
+        assert!(!failed_conclusion(Some("skipped")));
+        assert!(!failed_conclusion(None));
+    }
+
+    fn run(
+        id: usize,
+        created_at: &str,
+        duration_secs: i64,
+    ) -> Run {
+        let created_at: DateTime<Utc> = created_at.parse().unwrap();
+        Run {
+            id,
+            head_branch: "main".into(),
+            conclusion: Some("success".into()),
+            event: "push".into(),
+            status: "completed".into(),
+            jobs_url: String::new(),
+            logs_url: String::new(),
+            artifacts_url: String::new(),
+            cancel_url: String::new(),
+            rerun_url: String::new(),
+            created_at,
+            updated_at: created_at + chrono::Duration::seconds(duration_secs),
+            html_url: String::new(),
+            actor: None,
+            name: Some("ci".into()),
+            workflow_id: 1,
+        }
+    }
+
+    #[test]
+    fn sort_compares_runs_by_field_and_order() {
+        let mut runs = vec![
+            run(1, "2020-03-02T00:00:00Z", 60),
+            run(2, "2020-03-01T00:00:00Z", 600),
+            run(3, "2020-03-03T00:00:00Z", 30),
+        ];
+        let ids = |runs: &[Run]| runs.iter().map(|run| run.id).collect::<Vec<_>>();
+        runs.sort_by(|a, b| Sort::Created.compare(a, b, Order::Asc));
+        assert_eq!(ids(&runs), vec![2, 1, 3]);
+        runs.sort_by(|a, b| Sort::Updated.compare(a, b, Order::Desc));
+        assert_eq!(ids(&runs), vec![3, 1, 2]);
+        runs.sort_by(|a, b| Sort::Duration.compare(a, b, Order::Desc));
+        assert_eq!(ids(&runs), vec![2, 1, 3]);
+        assert!("name".parse::<Sort>().is_err());
+    }
+
+    #[test]
+    fn branch_counts_count_successes_and_failures_per_branch() {
+        let mut runs = vec![
+            run(1, "2020-03-01T00:00:00Z", 60),
+            run(2, "2020-03-02T00:00:00Z", 60),
+            run(3, "2020-03-03T00:00:00Z", 60),
+            run(4, "2020-03-04T00:00:00Z", 60),
+        ];
+        runs[1].conclusion = Some("cancelled".into());
+        runs[2].head_branch = "feature".into();
+        runs[3].head_branch = "feature".into();
+        runs[3].conclusion = None;
+        let counts = branch_counts(&runs);
+        assert_eq!(counts.get("main"), Some(&(1, 1)));
+        assert_eq!(counts.get("feature"), Some(&(1, 0)));
+    }
+
+    #[test]
+    fn run_rows_render_runs_and_their_jobs() {
+        let mut run = run(1, "2020-03-12T10:00:00Z", 200);
+        run.html_url = "https://github.com/owner/repo/actions/runs/1".into();
+        run.actor = Some(User {
+            login: "octocat".into(),
+        });
+        let job = Job {
+            html_url: "https://github.com/owner/repo/runs/2".into(),
+            status: "completed".into(),
+            conclusion: Some("failure".into()),
+            started_at: Some(run.created_at),
+            completed_at: Some(run.created_at + chrono::Duration::seconds(90)),
+            name: "test".into(),
+        };
+        let rows = RunRows {
+            time_format: None,
+            local_time: false,
+            relative_to: None,
+            duration_format: DurationFormat::Seconds,
+            with_jobs: true,
+        };
+        let mut out = Vec::new();
+        let mut table = Table::with_writer(Format::Csv, &mut out);
+        rows.write(&mut table, "CI", &run, &[job]).unwrap();
+        drop(table);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "CI,1,2020-03-12T10:00:00Z,success,200,https://github.com/owner/repo/actions/runs/1\n\
+             ,  by octocat,,,,\n\
+             ,  test,,failure,90,https://github.com/owner/repo/runs/2\n"
+        );
+    }
+
+    #[test]
+    fn run_rows_write_csv_times_in_the_date_format() {
+        let rows = RunRows {
+            time_format: Some("%d/%m/%Y".into()),
+            local_time: false,
+            relative_to: None,
+            duration_format: DurationFormat::Seconds,
+            with_jobs: false,
+        };
+        let mut out = Vec::new();
+        let mut table = Table::with_writer(Format::Csv, &mut out);
+        rows.write(&mut table, "CI", &run(1, "2020-03-12T10:00:00Z", 200), &[])
+            .unwrap();
+        drop(table);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "CI,1,12/03/2020,success,200,\n"
+        );
+    }
+
+    #[test]
+    fn duration_format_writes_whole_seconds_or_milliseconds() {
+        let duration = Duration::from_millis(200_500);
+        assert_eq!(DurationFormat::Human.format(duration), "3m 20s 500ms");
+        assert_eq!(DurationFormat::Seconds.format(duration), "200");
+        assert_eq!(DurationFormat::Millis.format(duration), "200500");
+        assert!("minutes".parse::<DurationFormat>().is_err());
+    }
+
+    #[test]
+    fn summary_aggregates_conclusions_and_durations() {
+        let mut summary = Summary::default();
+        assert_eq!(summary.average(), Duration::default());
+        assert_eq!(summary.p95(), Duration::default());
+        for (conclusion, secs) in
+            (1..=20).map(|secs| (if secs % 5 == 0 { "failure" } else { "success" }, secs * 60))
+        {
+            let created_at = Utc.ymd(2020, 3, 12).and_hms(0, 0, 0);
+            summary.add(&Run {
+                id: 1,
+                head_branch: "main".into(),
+                conclusion: Some(conclusion.into()),
+                event: "push".into(),
+                status: "completed".into(),
+                jobs_url: String::new(),
+                logs_url: String::new(),
+                artifacts_url: String::new(),
+                cancel_url: String::new(),
+                rerun_url: String::new(),
+                created_at,
+                updated_at: created_at + chrono::Duration::seconds(secs),
+                html_url: String::new(),
+                actor: None,
+                name: Some("ci".into()),
+                workflow_id: 1,
+            });
+        }
+        assert_eq!(summary.total(), 20);
+        assert_eq!(summary.conclusions.get("success"), Some(&16));
+        assert_eq!(summary.conclusions.get("failure"), Some(&4));
+        assert_eq!(summary.average(), Duration::from_secs(630));
+        assert_eq!(summary.p95(), Duration::from_secs(19 * 60));
+    }
 }
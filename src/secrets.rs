@@ -1,92 +1,695 @@
 //! Interfaces for accessing and updating GitHub secrets
-use crate::{github::Requests, StringErr};
-use futures::stream::StreamExt;
-use reqwest::Client;
-use sodiumoxide::crypto::box_::{self, PublicKey};
-use std::{env, error::Error, pin::Pin};
+use crate::{
+    confirm,
+    format::{Format, Table},
+    github::{Key, Requests, Secret, SecretScope, SecretValue},
+    print_fields, print_json, print_json_line, print_json_lines,
+    runs::format_time,
+    Config, Output, StringErr,
+};
+use colored::Colorize;
+use futures::{future, stream, StreamExt, TryStreamExt};
+use sodiumoxide::crypto::{box_::PublicKey, sealedbox};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fs,
+    io::{self, stderr, stdin, Read, Write},
+    path::PathBuf,
+    pin::Pin,
+    process::exit,
+};
 use structopt::StructOpt;
 
+/// Repository, repository environment or organization that owns secrets
+#[derive(StructOpt, Debug)]
+pub struct SecretOwner {
+    /// GitHub organization. Takes precedence over --repository to target organization secrets
+    #[structopt(long)]
+    pub(crate) org: Option<String>,
+    /// Deployment environment of the repository, i.e. production, to target its secrets
+    #[structopt(short, long, conflicts_with = "org")]
+    pub(crate) environment: Option<String>,
+}
+
+impl SecretOwner {
+    /// Resolves the api scope of these secrets. Environment secrets are addressed by their
+    /// repository's id, which is fetched once here for the rest of the command
+    async fn scope(
+        self,
+        config: &Config,
+        requests: &Requests,
+    ) -> Result<SecretScope, Box<dyn Error>> {
+        if let Some(org) = self.org {
+            return Ok(SecretScope::Org(org));
+        }
+        let repository = config
+            .repository()
+            .map_err(|_| StringErr("Please provide either a --repository or an --org".into()))?;
+        match self.environment {
+            Some(name) => Ok(SecretScope::Environment {
+                repository_id: requests.repo_id(&repository).await?,
+                name,
+            }),
+            None => Ok(SecretScope::Repo(repository)),
+        }
+    }
+}
+
 /// 🤫 Interact with workflow secrets
 #[derive(StructOpt, Debug)]
 pub enum Secrets {
     /// List repository secrets
     List {
-        /// GitHub repository in the form owner/repo
-        #[structopt(short, long, env = "ACTIONS_REPOSITORY")]
-        repository: String,
+        #[structopt(flatten)]
+        owner: SecretOwner,
     },
-    /// Get a public key used for creating secrets
+    /// Check whether a secret exists, exiting with status 0 when it does and 1 when it doesn't
+    Exists {
+        #[structopt(flatten)]
+        owner: SecretOwner,
+        /// Secret name
+        #[structopt(short, long)]
+        name: String,
+        /// Print the secret's name when it exists
+        #[structopt(long)]
+        print: bool,
+    },
+    /// Get a public key used for creating secrets. Json output also includes the key's id,
+    /// which secrets encrypted with it are created with
     PublicKey {
-        /// GitHub repository in the form owner/repo
-        #[structopt(short, long, env = "ACTIONS_REPOSITORY")]
-        repository: String,
+        #[structopt(flatten)]
+        owner: SecretOwner,
     },
-    /// Create a secret
+    /// Create or update a secret
+    #[structopt(visible_alias = "update")]
     Create {
-        /// GitHub repository in the form owner/repo
-        #[structopt(short, long, env = "ACTIONS_REPOSITORY")]
-        repository: String,
+        #[structopt(flatten)]
+        owner: SecretOwner,
         /// Secret name
         #[structopt(short, long)]
         name: String,
-        /// Secret value
-        #[structopt(short, long)]
-        value: String,
+        /// Secret value. Prefer --value-stdin or --value-file to keep values out of your shell history
+        #[structopt(short, long, required_unless_one = &["value-stdin", "value-file"])]
+        value: Option<String>,
+        /// Read the secret value from stdin. A single trailing newline is removed
+        #[structopt(long, conflicts_with_all = &["value", "value-file"])]
+        value_stdin: bool,
+        /// Read the secret value from a file
+        #[structopt(long, parse(from_os_str), conflicts_with = "value")]
+        value_file: Option<PathBuf>,
+        /// Which organization repositories may access the secret. Defaults to 'private', or 'selected' when --selected-repo is provided
+        #[structopt(long, requires = "org", possible_values = &["all", "private", "selected"])]
+        visibility: Option<String>,
+        /// Id of an organization repository that may access the secret. May be provided multiple times
+        #[structopt(long = "selected-repo", requires = "org")]
+        selected_repos: Vec<u64>,
+    },
+    /// Create or update secrets from a file of KEY=value lines
+    Import {
+        #[structopt(flatten)]
+        owner: SecretOwner,
+        /// File of KEY=value lines. Blank lines and lines starting with # are skipped
+        #[structopt(long, parse(from_os_str))]
+        env_file: PathBuf,
+    },
+    /// Create the secrets of one repository that are missing from another. Secret values
+    /// can not be read, so each value is prompted for or read from an env file
+    Copy {
+        /// GitHub repository to copy secrets from in the form owner/repo
+        #[structopt(long)]
+        from: String,
+        /// GitHub repository to copy secrets to in the form owner/repo
+        #[structopt(long)]
+        to: String,
+        /// File of KEY=value lines to read values from instead of prompting for them.
+        /// Secrets missing from the file are skipped
+        #[structopt(long, parse(from_os_str))]
+        env_file: Option<PathBuf>,
     },
     Delete {
-        /// GitHub repository in the form owner/repo
-        #[structopt(short, long, env = "ACTIONS_REPOSITORY")]
-        repository: String,
+        #[structopt(flatten)]
+        owner: SecretOwner,
         /// Name of secret to delete
         // #[structopt(short, long)]
-        name: String,
+        #[structopt(required_unless = "prefix")]
+        name: Option<String>,
+        /// Delete every secret whose name starts with this prefix, ignoring case, in place of
+        /// a single secret
+        #[structopt(long, conflicts_with = "name")]
+        prefix: Option<String>,
+        /// Delete without asking for confirmation
+        #[structopt(short, long)]
+        yes: bool,
     },
 }
 
-pub async fn secrets(args: Secrets) -> Result<(), Box<dyn Error>> {
+pub async fn secrets(
+    args: Secrets,
+    config: Config,
+) -> Result<(), Box<dyn Error>> {
     match args {
-        Secrets::List { repository } => {
-            let client = Client::new();
-            let token = env::var("GITHUB_TOKEN")
-                .map_err(|_| StringErr("Please provide a GITHUB_TOKEN env variable".into()))?;
-            let requests = Requests { client, token };
-            let mut secrets = requests.clone().secrets(repository).boxed();
-            while let Some(secret) = Pin::new(&mut secrets).next().await {
-                println!("{}", secret.name);
+        Secrets::List { owner } => {
+            let requests = config.requests()?;
+            let scope = owner.scope(&config, &requests).await?;
+            let mut secrets = requests.clone().secrets(scope).take(config.limit()).boxed();
+            if let Some(fields) = &config.fields {
+                return print_fields(&config, fields, Format::Tab, secrets).await;
+            }
+            match config.output {
+                Output::Json => return print_json(&secrets.try_collect::<Vec<_>>().await?),
+                Output::JsonLines => return print_json_lines(secrets).await,
+                Output::Text => (),
+            }
+            let mut table = Table::new(Format::Tab).quiet(config.quiet);
+            table.header(&[&"Name", &"Updated", &""])?;
+            while let Some(secret) = Pin::new(&mut secrets).try_next().await? {
+                secret_row(&mut table, &secret, config.date_format.as_deref())?;
             }
+            table.flush()?;
         }
-        Secrets::PublicKey { repository } => {
-            let client = Client::new();
-            let token = env::var("GITHUB_TOKEN")?;
-            let requests = Requests { client, token };
-            println!("{}", requests.public_key(repository).await?.key);
+        Secrets::Exists { owner, name, print } => {
+            let requests = config.requests()?;
+            let scope = owner.scope(&config, &requests).await?;
+            // stops paging as soon as the secret is found
+            let found = requests
+                .clone()
+                .secrets(scope)
+                .try_filter(|secret| future::ready(secret.name.eq_ignore_ascii_case(&name)))
+                .boxed()
+                .try_next()
+                .await?;
+            match found {
+                Some(secret) if print => println!("{}", secret.name),
+                Some(_) => (),
+                None => exit(1),
+            }
         }
-        Secrets::Delete { repository, name } => {
-            let client = Client::new();
-            let token = env::var("GITHUB_TOKEN")?;
-            let requests = Requests { client, token };
-            requests.delete_secret(repository, name.clone()).await?;
-            println!("Secret {} is deleted", name);
+        Secrets::PublicKey { owner } => {
+            let requests = config.requests()?;
+            let scope = owner.scope(&config, &requests).await?;
+            let key = requests.public_key(&scope).await?;
+            match config.output {
+                Output::Json => println!("{}", serde_json::to_string_pretty(&key)?),
+                Output::JsonLines => print_json_line(&key)?,
+                Output::Text => println!("{}", key.key),
+            }
+        }
+        Secrets::Delete {
+            owner,
+            name: Some(name),
+            yes,
+            ..
+        } => {
+            if !yes && !config.dry_run && !confirm(&format!("Delete secret {}?", name))? {
+                return Ok(());
+            }
+            let requests = config.requests()?;
+            let scope = owner.scope(&config, &requests).await?;
+            requests.delete_secret(scope, name.clone()).await?;
+            config.changed(format!("Secret {} is deleted", name));
+        }
+        Secrets::Delete {
+            owner,
+            name: None,
+            prefix,
+            yes,
+        } => {
+            let prefix = prefix.unwrap_or_default();
+            let requests = config.requests()?;
+            let scope = owner.scope(&config, &requests).await?;
+            let names = requests
+                .clone()
+                .secrets(scope.clone())
+                .map_ok(|secret| secret.name)
+                .try_collect::<Vec<_>>()
+                .await?;
+            let names = with_prefix(names, &prefix);
+            if names.is_empty() {
+                println!("No secrets start with {}", prefix);
+                return Ok(());
+            }
+            if !yes
+                && !config.dry_run
+                && !confirm(&format!(
+                    "Delete {} secret(s) {}?",
+                    names.len(),
+                    names.join(", ")
+                ))?
+            {
+                return Ok(());
+            }
+            let mut deletes = stream::iter(names)
+                .map(|name| {
+                    let requests = requests.clone();
+                    let scope = scope.clone();
+                    async move {
+                        let deleted = requests.delete_secret(scope, name.clone()).await;
+                        (name, deleted)
+                    }
+                })
+                .buffer_unordered(config.concurrency);
+            let (mut deleted, mut failed) = (0, 0);
+            while let Some((name, result)) = deletes.next().await {
+                match result {
+                    Ok(()) => {
+                        deleted += 1;
+                        config.changed(format!("Secret {} is deleted", name));
+                    }
+                    Err(err) => {
+                        failed += 1;
+                        eprintln!(
+                            "{}",
+                            format!("Secret {} could not be deleted: {}", name, err).red()
+                        );
+                    }
+                }
+            }
+            config.changed(format!("Deleted {} secret(s)", deleted));
+            if failed > 0 {
+                return Err(StringErr(format!("{} secret(s) could not be deleted", failed)).into());
+            }
         }
         Secrets::Create {
-            repository,
+            owner,
             name,
             value,
+            value_stdin,
+            value_file,
+            visibility,
+            selected_repos,
         } => {
-            let client = Client::new();
-            let token = env::var("GITHUB_TOKEN")?;
-            let requests = Requests { client, token };
-            let crate::github::Key { key_id, key } = requests.public_key(&repository).await?;
-            let theirs = PublicKey::from_slice(&base64::decode(key)?).unwrap();
-            let (_, ours) = box_::gen_keypair();
-            let nonce = box_::gen_nonce();
-            let encrypted = box_::seal(&value.as_bytes(), &nonce, &theirs, &ours);
-            let encrypted_value = base64::encode(encrypted);
+            let value = secret_value(value, value_stdin, value_file)?;
+            let requests = config.requests()?;
+            let scope = owner.scope(&config, &requests).await?;
+            let Key { key_id, key } = requests.public_key(&scope).await?;
+            let encrypted_value = encrypt_secret(&key, &value)?;
+            let (visibility, selected_repository_ids) =
+                secret_access(&scope, visibility, selected_repos);
             requests
-                .upsert_secret(repository, name, encrypted_value, key_id)
+                .upsert_secret(
+                    scope,
+                    name,
+                    SecretValue {
+                        encrypted_value,
+                        key_id,
+                        visibility,
+                        selected_repository_ids,
+                    },
+                )
                 .await?;
         }
+        Secrets::Import { owner, env_file } => {
+            let entries = parse_env(&fs::read_to_string(&env_file)?)?;
+            let requests = config.requests()?;
+            let scope = owner.scope(&config, &requests).await?;
+            let key = requests.public_key(&scope).await?;
+            let (summary, failed) = import_secrets(&requests, &scope, &key, entries).await;
+            println!("{}", summary);
+            if !failed.is_empty() {
+                return Err(StringErr(format!(
+                    "{} secret(s) could not be imported: {}",
+                    failed.len(),
+                    failed.join(", ")
+                ))
+                .into());
+            }
+        }
+        Secrets::Copy { from, to, env_file } => {
+            let mut values = match env_file {
+                Some(path) => Some(
+                    parse_env(&fs::read_to_string(&path)?)?
+                        .into_iter()
+                        .collect::<BTreeMap<_, _>>(),
+                ),
+                None => None,
+            };
+            let requests = config.requests()?;
+            let names = |repository: String| {
+                requests
+                    .clone()
+                    .secrets(SecretScope::Repo(repository))
+                    .map_ok(|secret| secret.name)
+                    .try_collect::<Vec<_>>()
+            };
+            let missing = missing_secrets(names(from).await?, names(to.clone()).await?);
+            if missing.is_empty() {
+                println!("All secrets are already present on {}", to);
+                return Ok(());
+            }
+            let scope = SecretScope::Repo(to);
+            let Key { key_id, key } = requests.public_key(&scope).await?;
+            let (mut created, mut skipped) = (Vec::new(), Vec::new());
+            for name in missing {
+                let value = match values.as_mut() {
+                    Some(values) => values.remove(&name),
+                    None => prompt_value(&name)?,
+                };
+                let value = match value {
+                    Some(value) => value,
+                    None => {
+                        skipped.push(name);
+                        continue;
+                    }
+                };
+                requests
+                    .clone()
+                    .upsert_secret(
+                        scope.clone(),
+                        name.clone(),
+                        SecretValue {
+                            encrypted_value: encrypt_secret(&key, value.as_bytes())?,
+                            key_id: key_id.clone(),
+                            visibility: None,
+                            selected_repository_ids: None,
+                        },
+                    )
+                    .await?;
+                created.push(name);
+            }
+            if !created.is_empty() {
+                config.changed(format!("Created {}", created.join(", ").green()));
+            }
+            if !skipped.is_empty() {
+                println!(
+                    "Skipped {} with unknown values",
+                    skipped.join(", ").yellow()
+                );
+            }
+        }
     }
 
     Ok(())
 }
+
+/// Encrypts and saves each entry as a secret, returning a line summarizing what was saved
+/// along with the names of secrets which could not be. Dry runs only count the secrets
+/// which would be saved, as whether each would be created or updated isn't known
+async fn import_secrets(
+    requests: &Requests,
+    scope: &SecretScope,
+    key: &Key,
+    entries: Vec<(String, String)>,
+) -> (String, Vec<String>) {
+    let (visibility, selected_repository_ids) = secret_access(scope, None, Vec::new());
+    let (mut created, mut updated, mut failed) = (0, 0, Vec::new());
+    for (name, value) in entries {
+        let result = match encrypt_secret(&key.key, value.as_bytes()) {
+            Ok(encrypted_value) => requests
+                .clone()
+                .upsert_secret(
+                    scope.clone(),
+                    name.clone(),
+                    SecretValue {
+                        encrypted_value,
+                        key_id: key.key_id.clone(),
+                        visibility: visibility.clone(),
+                        selected_repository_ids: selected_repository_ids.clone(),
+                    },
+                )
+                .await
+                .map_err(Into::into),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(true) => created += 1,
+            Ok(false) => updated += 1,
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    format!("Secret {} could not be saved: {}", name, err).red()
+                );
+                failed.push(name);
+            }
+        }
+    }
+    let summary = if requests.dry_run {
+        format!("Would create or update {} secrets", created + updated)
+    } else {
+        format!("Created {} and updated {} secrets", created, updated)
+    };
+    (summary, failed)
+}
+
+/// Who may access a secret. Only organization secrets have a visibility, which defaults to
+/// 'private', or 'selected' when repositories are selected
+fn secret_access(
+    scope: &SecretScope,
+    visibility: Option<String>,
+    selected_repos: Vec<u64>,
+) -> (Option<String>, Option<Vec<u64>>) {
+    match scope {
+        SecretScope::Org(_) => (
+            visibility.or_else(|| {
+                Some(if selected_repos.is_empty() {
+                    "private".into()
+                } else {
+                    "selected".into()
+                })
+            }),
+            Some(selected_repos).filter(|ids| !ids.is_empty()),
+        ),
+        _ => (None, None),
+    }
+}
+
+/// Parses the KEY=value lines of an env file, skipping blank lines and # comments.
+/// Keys may be prefixed with `export` and values may be wrapped in matching quotes
+fn parse_env(contents: &str) -> Result<Vec<(String, String)>, StringErr> {
+    contents
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (name, value) = line
+                .split_once('=')
+                .filter(|(name, _)| !name.trim().is_empty())
+                .ok_or_else(|| {
+                    StringErr(format!("line {} is not a valid KEY=value pair", number))
+                })?;
+            let value = value.trim();
+            let value = ['"', '\'']
+                .iter()
+                .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+                .unwrap_or(value);
+            Ok((name.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Writes a listed secret as a table row. Only organization secrets have a visibility
+fn secret_row(
+    table: &mut Table,
+    secret: &Secret,
+    date_format: Option<&str>,
+) -> io::Result<()> {
+    table.row(&[
+        &secret.name.bold(),
+        &secret
+            .updated_at
+            .map_or_else(|| "-".to_string(), |at| format_time(at, date_format, false)),
+        &secret.visibility.as_deref().unwrap_or_default().dimmed(),
+    ])
+}
+
+/// Names of secrets starting with a prefix. Secret names are case insensitive
+fn with_prefix(
+    names: Vec<String>,
+    prefix: &str,
+) -> Vec<String> {
+    let prefix = prefix.to_uppercase();
+    names
+        .into_iter()
+        .filter(|name| name.to_uppercase().starts_with(&prefix))
+        .collect()
+}
+
+/// Names of source secrets which the destination does not have yet
+fn missing_secrets(
+    source: Vec<String>,
+    destination: Vec<String>,
+) -> Vec<String> {
+    let destination = destination.into_iter().collect::<BTreeSet<_>>();
+    source
+        .into_iter()
+        .filter(|name| !destination.contains(name))
+        .collect()
+}
+
+/// Prompts for a secret's value on stderr, reading it from a line of stdin.
+/// An empty line skips the secret
+fn prompt_value(name: &str) -> Result<Option<String>, Box<dyn Error>> {
+    eprint!("Value for {} (leave empty to skip): ", name.bold());
+    stderr().flush()?;
+    let mut line = String::new();
+    stdin().read_line(&mut line)?;
+    let value = line.trim_end_matches(&['\r', '\n'][..]);
+    Ok(Some(value.to_string()).filter(|value| !value.is_empty()))
+}
+
+/// Resolves a secret's value from exactly one of its possible sources
+fn secret_value(
+    value: Option<String>,
+    value_stdin: bool,
+    value_file: Option<PathBuf>,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    if value_stdin {
+        let mut value = Vec::new();
+        stdin().read_to_end(&mut value)?;
+        if value.ends_with(b"\n") {
+            value.pop();
+            if value.ends_with(b"\r") {
+                value.pop();
+            }
+        }
+        return Ok(value);
+    }
+    if let Some(path) = value_file {
+        return Ok(fs::read(path)?);
+    }
+    value
+        .map(String::into_bytes)
+        .ok_or_else(|| StringErr("Please provide a secret value".into()).into())
+}
+
+/// Encrypts a secret value with a repository's base64 encoded public key
+/// using a libsodium sealed box, returning the base64 encoded result
+/// expected by the secrets api
+pub fn encrypt_secret(
+    public_key: &str,
+    value: &[u8],
+) -> Result<String, Box<dyn Error>> {
+    let key = PublicKey::from_slice(&base64::decode(public_key)?)
+        .ok_or_else(|| StringErr(format!("{} is not a valid public key", public_key)))?;
+    Ok(base64::encode(sealedbox::seal(value, &key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::{Credentials, DEFAULT_API_VERSION, DEFAULT_USER_AGENT};
+    use sodiumoxide::crypto::box_;
+
+    #[test]
+    fn encrypt_secret_can_be_opened_with_secret_key() {
+        let (public, secret) = box_::gen_keypair();
+        let encrypted = encrypt_secret(&base64::encode(public), b"hunter2").unwrap();
+        assert_eq!(
+            sealedbox::open(&base64::decode(encrypted).unwrap(), &public, &secret),
+            Ok(b"hunter2".to_vec())
+        )
+    }
+
+    #[test]
+    fn parse_env_reads_pairs_and_skips_comments() {
+        assert_eq!(
+            parse_env("# deploy\n\nTOKEN=abc=123\nexport NAME = \"octo cat\"\nEMPTY=\n").unwrap(),
+            vec![
+                ("TOKEN".into(), "abc=123".into()),
+                ("NAME".into(), "octo cat".into()),
+                ("EMPTY".into(), "".into())
+            ]
+        );
+        assert!(parse_env("TOKEN").is_err());
+    }
+
+    #[test]
+    fn secret_rows_include_the_date_updated() {
+        let mut out = Vec::new();
+        let mut table = Table::with_writer(Format::Csv, &mut out);
+        secret_row(
+            &mut table,
+            &Secret {
+                name: "NPM_TOKEN".into(),
+                visibility: Some("private".into()),
+                created_at: None,
+                updated_at: Some("2020-03-12T10:00:00Z".parse().unwrap()),
+            },
+            None,
+        )
+        .unwrap();
+        secret_row(
+            &mut table,
+            &Secret {
+                name: "DEPLOY_KEY".into(),
+                visibility: None,
+                created_at: None,
+                updated_at: None,
+            },
+            None,
+        )
+        .unwrap();
+        drop(table);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "NPM_TOKEN,2020-03-12T10:00:00Z,private\nDEPLOY_KEY,-,\n"
+        );
+    }
+
+    #[test]
+    fn with_prefix_matches_names_ignoring_case() {
+        assert_eq!(
+            with_prefix(
+                vec![
+                    "STAGING_DB_URL".into(),
+                    "PROD_DB_URL".into(),
+                    "STAGING_KEY".into()
+                ],
+                "staging_"
+            ),
+            vec!["STAGING_DB_URL".to_string(), "STAGING_KEY".to_string()]
+        );
+    }
+
+    #[test]
+    fn missing_secrets_excludes_those_the_destination_has() {
+        assert_eq!(
+            missing_secrets(
+                vec!["NPM_TOKEN".into(), "DEPLOY_KEY".into(), "SLACK_HOOK".into()],
+                vec!["SLACK_HOOK".into(), "OTHER".into()]
+            ),
+            vec!["NPM_TOKEN".to_string(), "DEPLOY_KEY".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn import_secrets_dry_run_reports_no_changes() {
+        let requests = Requests {
+            client: reqwest::Client::new(),
+            credentials: Credentials::Token("token".into()),
+            // nothing listens here, as dry runs send nothing
+            base_url: "http://127.0.0.1:9".into(),
+            max_retries: 0,
+            per_page: 1,
+            cache: None,
+            user_agent: DEFAULT_USER_AGENT.into(),
+            api_version: DEFAULT_API_VERSION.into(),
+            throttle: None,
+            dry_run: true,
+        };
+        let (public, _) = box_::gen_keypair();
+        let key = Key {
+            key_id: "1".into(),
+            key: base64::encode(public),
+        };
+        let (summary, failed) = import_secrets(
+            &requests,
+            &SecretScope::Repo("owner/repo".into()),
+            &key,
+            vec![
+                ("NPM_TOKEN".into(), "abc".into()),
+                ("DEPLOY_KEY".into(), "def".into()),
+            ],
+        )
+        .await;
+        assert_eq!(summary, "Would create or update 2 secrets");
+        assert!(failed.is_empty());
+    }
+
+    #[test]
+    fn encrypt_secret_rejects_invalid_keys() {
+        assert!(encrypt_secret(&base64::encode(b"short"), b"hunter2").is_err())
+    }
+}
@@ -0,0 +1,70 @@
+//! Client side rate limiting, which spaces requests out so that they stay under a rate
+//! rather than waiting out GitHub's rate limits once they're exceeded
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{sync::Mutex, time::delay_for};
+
+/// Spaces requests made through any of its clones at least an interval apart
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    interval: Duration,
+    next: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Throttle {
+    /// Throttles requests to at most `per_second` a second
+    pub fn new(per_second: f64) -> Self {
+        Throttle {
+            interval: Duration::from_secs_f64(1.0 / per_second),
+            next: Arc::default(),
+        }
+    }
+
+    /// Waits until a request may be sent. `jitter`, a fraction between 0 and 1, lengthens
+    /// the wait of the following request by up to a tenth of an interval
+    pub async fn wait(
+        &self,
+        jitter: f64,
+    ) {
+        let now = Instant::now();
+        let slot = reserve(&mut *self.next.lock().await, now, self.interval, jitter);
+        if slot > now {
+            delay_for(slot - now).await;
+        }
+    }
+}
+
+/// Reserves the earliest free slot at or after `now`, moving the next free slot
+/// an interval, plus jitter, later
+fn reserve(
+    next: &mut Option<Instant>,
+    now: Instant,
+    interval: Duration,
+    jitter: f64,
+) -> Instant {
+    let slot = next.map_or(now, |next| next.max(now));
+    *next = Some(slot + interval + interval.mul_f64(jitter.clamp(0.0, 1.0) / 10.0));
+    slot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve_spaces_slots_an_interval_apart() {
+        let (now, interval) = (Instant::now(), Duration::from_millis(100));
+        let mut next = None;
+        assert_eq!(reserve(&mut next, now, interval, 0.0), now);
+        assert_eq!(reserve(&mut next, now, interval, 1.0), now + interval);
+        assert_eq!(
+            reserve(&mut next, now, interval, 0.0),
+            now + interval * 2 + interval / 10
+        );
+        // slots aren't saved up while no requests are made
+        let later = now + interval * 10;
+        assert_eq!(reserve(&mut next, later, interval, 0.0), later);
+    }
+}
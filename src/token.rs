@@ -0,0 +1,196 @@
+//! Resolution of the GitHub token used to authenticate api requests
+use crate::StringErr;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::Serialize;
+use std::{
+    env, fs,
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+use url::Url;
+
+/// A GitHub App installation whose access tokens are used in place of a personal token
+#[derive(Debug, Clone)]
+pub struct App {
+    pub id: u64,
+    pub installation_id: u64,
+    key: EncodingKey,
+}
+
+/// Claims identifying a GitHub App, see the
+/// [developer docs](https://docs.github.com/en/apps/creating-github-apps/authenticating-with-a-github-app/generating-a-json-web-token-jwt-for-a-github-app)
+#[derive(Serialize)]
+struct AppClaims {
+    iat: i64,
+    exp: i64,
+    iss: String,
+}
+
+impl App {
+    /// Loads an app's RSA private key from a pem file downloaded from its settings
+    pub(crate) fn new(
+        id: u64,
+        installation_id: u64,
+        private_key_file: &Path,
+    ) -> Result<Self, StringErr> {
+        let pem = fs::read(private_key_file).map_err(|err| {
+            StringErr(format!(
+                "Failed to read private key file {}: {}",
+                private_key_file.display(),
+                err
+            ))
+        })?;
+        let key = EncodingKey::from_rsa_pem(&pem).map_err(|err| {
+            StringErr(format!(
+                "{} is not a valid RSA private key: {}",
+                private_key_file.display(),
+                err
+            ))
+        })?;
+        Ok(App {
+            id,
+            installation_id,
+            key,
+        })
+    }
+
+    /// Creates a short lived JWT which may be exchanged for an installation access token.
+    /// It is issued a minute in the past to allow for clock drift
+    pub fn jwt(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        jsonwebtoken::encode(
+            &Header::new(Algorithm::RS256),
+            &AppClaims {
+                iat: (now - Duration::seconds(60)).timestamp(),
+                exp: (now + Duration::minutes(9)).timestamp(),
+                iss: self.id.to_string(),
+            },
+            &self.key,
+        )
+    }
+}
+
+/// Resolves a GitHub token by checking, in order
+///
+/// * the `GITHUB_TOKEN` env variable
+/// * the contents of `token_file`
+/// * the `oauth_token` stored by the gh cli in its `hosts.yml` for the api's host
+pub fn resolve_token(
+    token_file: Option<&Path>,
+    api_url: &str,
+) -> Result<String, StringErr> {
+    if let Ok(token) = env::var("GITHUB_TOKEN") {
+        return Ok(token);
+    }
+    if let Some(path) = token_file {
+        return fs::read_to_string(path)
+            .map(|token| token.trim().to_string())
+            .map_err(|err| {
+                StringErr(format!(
+                    "Failed to read token file {}: {}",
+                    path.display(),
+                    err
+                ))
+            });
+    }
+    gh_hosts_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|hosts| gh_token(&hosts, &gh_host(api_url)?))
+        .ok_or_else(|| {
+            StringErr(
+                "Please provide a token. Tokens are resolved from a GITHUB_TOKEN env variable, \
+                 then a --token-file, then the gh cli's hosts.yml (see `gh auth login`)"
+                    .into(),
+            )
+        })
+}
+
+/// Reads a token from the first line of `input`, i.e. stdin for `--token-stdin`
+pub fn read_token(mut input: impl BufRead) -> Result<String, StringErr> {
+    let mut line = String::new();
+    input
+        .read_line(&mut line)
+        .map_err(|err| StringErr(format!("Failed to read token from stdin: {}", err)))?;
+    match line.trim() {
+        "" => Err(StringErr("No token was provided on stdin".into())),
+        token => Ok(token.into()),
+    }
+}
+
+/// Location of the gh cli's hosts file
+fn gh_hosts_path() -> Option<PathBuf> {
+    env::var_os("GH_CONFIG_DIR")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("XDG_CONFIG_HOME").map(|dir| PathBuf::from(dir).join("gh")))
+        .or_else(|| env::var_os("HOME").map(|dir| PathBuf::from(dir).join(".config").join("gh")))
+        .map(|dir| dir.join("hosts.yml"))
+}
+
+/// The host gh stores credentials under for a given api url
+pub(crate) fn gh_host(api_url: &str) -> Option<String> {
+    match Url::parse(api_url).ok()?.host_str()? {
+        "api.github.com" => Some("github.com".into()),
+        host => Some(host.into()),
+    }
+}
+
+/// Extracts a host's `oauth_token` from the contents of a gh hosts.yml file
+fn gh_token(
+    hosts: &str,
+    host: &str,
+) -> Option<String> {
+    hosts
+        .lines()
+        .skip_while(|line| line.trim_end() != format!("{}:", host))
+        .skip(1)
+        .take_while(|line| line.starts_with(char::is_whitespace))
+        .find_map(|line| {
+            let value = line.trim().strip_prefix("oauth_token:")?.trim();
+            Some(value.trim_matches(|c| c == '"' || c == '\'').to_string())
+        })
+        .filter(|token| !token.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOSTS: &str = "github.com:
+    user: octocat
+    oauth_token: gho_public
+    git_protocol: https
+github.mycorp.com:
+    oauth_token: \"gho_enterprise\"
+";
+
+    #[test]
+    fn read_token_reads_the_first_line() {
+        assert_eq!(
+            read_token(&b"ghs_token\nsecret value\n"[..]).unwrap(),
+            "ghs_token"
+        );
+        assert!(read_token(&b"\n"[..]).is_err());
+    }
+
+    #[test]
+    fn gh_host_maps_public_api_to_github() {
+        assert_eq!(gh_host("https://api.github.com"), Some("github.com".into()));
+        assert_eq!(
+            gh_host("https://github.mycorp.com/api/v3"),
+            Some("github.mycorp.com".into())
+        );
+    }
+
+    #[test]
+    fn gh_token_reads_token_for_host() {
+        assert_eq!(gh_token(HOSTS, "github.com"), Some("gho_public".into()));
+        assert_eq!(
+            gh_token(HOSTS, "github.mycorp.com"),
+            Some("gho_enterprise".into())
+        );
+        assert_eq!(gh_token(HOSTS, "example.com"), None);
+    }
+}
@@ -0,0 +1,34 @@
+//! Checks which user the configured token authenticates as
+use crate::{print_json_line, Config, Output};
+use colored::Colorize;
+use serde::Serialize;
+use std::error::Error;
+
+/// The authenticated user as printed with `--output json`
+#[derive(Serialize)]
+struct Whoami {
+    login: String,
+    scopes: Option<Vec<String>>,
+}
+
+pub async fn whoami(config: Config) -> Result<(), Box<dyn Error>> {
+    let (user, scopes) = config.requests()?.user().await?;
+    let whoami = Whoami {
+        login: user.login,
+        scopes,
+    };
+    match config.output {
+        Output::Json => println!("{}", serde_json::to_string_pretty(&whoami)?),
+        Output::JsonLines => print_json_line(&whoami)?,
+        Output::Text => {
+            println!("Logged in as {}", whoami.login.bold());
+            match whoami.scopes {
+                Some(scopes) if scopes.is_empty() => println!("Token scopes {}", "none".dimmed()),
+                Some(scopes) => println!("Token scopes {}", scopes.join(", ")),
+                // fine-grained tokens are granted permissions rather than scopes
+                None => println!("Token scopes {}", "n/a".dimmed()),
+            }
+        }
+    }
+    Ok(())
+}
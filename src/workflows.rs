@@ -1,121 +1,666 @@
 use crate::{
-    github::{Requests, Workflow},
-    StringErr,
+    format::{Format, Table},
+    github::{ActionsError, Dispatch, Requests, RunFilter, Workflow, WorkflowUsage},
+    print_fields, print_json, print_json_line, print_json_lines,
+    repos::read_repos_file,
+    Config, Output, StringErr,
 };
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc};
 use colored::Colorize;
-use futures::{stream::Stream, StreamExt};
-use humantime::format_duration;
-use reqwest::Client;
-use std::{
-    env,
-    error::Error,
-    io::{stdout, Write},
-    pin::Pin,
-    time::Duration,
+use futures::{
+    future,
+    stream::{self, Stream},
+    StreamExt, TryStreamExt,
 };
+use humantime::format_duration;
+use serde::{Deserialize, Serialize, Serializer};
+use std::{collections::BTreeMap, error::Error, io, path::PathBuf, pin::Pin, time::Duration};
 use structopt::StructOpt;
-use tabwriter::TabWriter;
 
 /// 🤹 Get workflow information
 #[derive(StructOpt, Debug)]
 pub enum Workflows {
     /// List declared workflows
     List {
-        /// GitHub repository in the form owner/repo
-        #[structopt(short, long, env = "ACTIONS_REPOSITORY")]
-        repository: String,
-        /// Workflow name
+        /// Workflow name or file name, i.e. ci.yml. Matches any workflow containing it
         #[structopt(short, long, env = "ACTIONS_WORKFLOW")]
         workflow: Option<String>,
+        /// Only match workflows whose name or file name is exactly --workflow
+        #[structopt(long, requires = "workflow")]
+        exact: bool,
+        /// Also list workflows that aren't active, i.e. disabled or deleted ones
+        #[structopt(long)]
+        include_disabled: bool,
+        /// GitHub organization whose repos' workflows are all listed, in place of --repository.
+        /// Repos are discovered with code search, as in `actions repos`
+        #[structopt(long)]
+        org: Option<String>,
+        /// File of repositories whose workflows are all listed, in place of --repository, with
+        /// one owner/repo per line
+        #[structopt(long, parse(from_os_str), conflicts_with = "org")]
+        repos_file: Option<PathBuf>,
+        /// Format of output 'tab' (default), 'csv' or 'md', a markdown table
+        #[structopt(default_value = "tab", short, long, env = "ACTIONS_FORMAT")]
+        format: Format,
     },
-    /// List billable minutes declared workflows
+    /// List billable minutes declared workflows. Json output lists each workflow's
+    /// billable milliseconds by runner os along with their totals
     Usage {
-        /// GitHub repository in the form owner/repo
-        #[structopt(short, long, env = "ACTIONS_REPOSITORY")]
-        repository: String,
         /// Workflow name
         #[structopt(short, long, env = "ACTIONS_WORKFLOW")]
         workflow: Option<String>,
-    }, // todo: Show
+        /// Format of output 'tab' (default), 'csv' or 'md', a markdown table
+        #[structopt(default_value = "tab", short, long, env = "ACTIONS_FORMAT")]
+        format: Format,
+    },
+    /// List a repository's billable minutes by runner os, along with the included minutes
+    /// its owner has remaining when the token may read them
+    Billing {
+        /// Only count runs created during this month in yyyy-mm format. Counts the
+        /// current billing cycle by default
+        #[structopt(long, parse(try_from_str = parse_month))]
+        month: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    },
+    /// Trigger a workflow_dispatch event
+    Dispatch {
+        /// Workflow file name or name
+        #[structopt(short, long, env = "ACTIONS_WORKFLOW")]
+        workflow: String,
+        /// Git branch or tag to run the workflow on
+        #[structopt(long = "ref")]
+        git_ref: String,
+        /// Workflow input in the form key=value. May be provided multiple times
+        #[structopt(long = "input", parse(try_from_str = parse_input))]
+        inputs: Vec<(String, String)>,
+        /// Send inputs without checking them against those the workflow declares at --ref
+        #[structopt(long)]
+        no_validate: bool,
+    },
+    /// Enable a disabled workflow
+    Enable {
+        /// Workflow file name or name
+        #[structopt(short, long, env = "ACTIONS_WORKFLOW")]
+        workflow: String,
+    },
+    /// Disable a workflow so that it no longer runs
+    Disable {
+        /// Workflow file name or name
+        #[structopt(short, long, env = "ACTIONS_WORKFLOW")]
+        workflow: String,
+    },
+    /// Show a workflow's definition on the default branch
+    Show {
+        /// Workflow file name or name
+        #[structopt(short, long, env = "ACTIONS_WORKFLOW")]
+        workflow: String,
+        /// Only print the workflow's yaml
+        #[structopt(long)]
+        raw: bool,
+    },
+}
+
+/// A listed workflow along with its repository when listing an organization's workflows
+#[derive(Serialize)]
+struct Listed {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repository: Option<String>,
+    #[serde(flatten)]
+    workflow: Workflow,
+}
+
+/// Writes a listed workflow as a table row, tagging workflows which aren't active
+fn workflow_row(
+    table: &mut Table,
+    Listed {
+        repository,
+        workflow,
+    }: &Listed,
+) -> io::Result<()> {
+    let name = if workflow.active() {
+        workflow.name.bold().to_string()
+    } else {
+        format!("{} {}", workflow.name.bold(), "[disabled]".dimmed())
+    };
+    match repository {
+        Some(repository) => table.row(&[repository, &name, &workflow.path.dimmed()]),
+        None => table.row(&[&name, &workflow.path.dimmed()]),
+    }
+}
+
+/// A workflow's billable time by runner os, serialized as milliseconds
+#[derive(Serialize)]
+struct Usage {
+    workflow: String,
+    #[serde(serialize_with = "millis")]
+    ubuntu_ms: Duration,
+    #[serde(serialize_with = "millis")]
+    macos_ms: Duration,
+    #[serde(serialize_with = "millis")]
+    windows_ms: Duration,
+}
+
+/// Billable time summed across workflows, serialized as milliseconds
+#[derive(Serialize, Default)]
+struct UsageSummary {
+    #[serde(serialize_with = "millis")]
+    ubuntu_ms: Duration,
+    #[serde(serialize_with = "millis")]
+    macos_ms: Duration,
+    #[serde(serialize_with = "millis")]
+    windows_ms: Duration,
+    #[serde(serialize_with = "millis")]
+    total_ms: Duration,
+    /// Number of workflows whose usage could not be fetched
+    skipped: usize,
+}
+
+impl UsageSummary {
+    fn add(
+        &mut self,
+        usage: &Usage,
+    ) {
+        self.ubuntu_ms += usage.ubuntu_ms;
+        self.macos_ms += usage.macos_ms;
+        self.windows_ms += usage.windows_ms;
+        self.total_ms += usage.ubuntu_ms + usage.macos_ms + usage.windows_ms;
+    }
+}
+
+/// Json output of `workflows usage`
+#[derive(Serialize)]
+struct UsageReport {
+    workflows: Vec<Usage>,
+    summary: UsageSummary,
+}
+
+fn millis<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
+fn parse_input(input: &str) -> Result<(String, String), String> {
+    match input.find('=') {
+        Some(idx) => Ok((input[..idx].into(), input[idx + 1..].into())),
+        None => Err(format!(
+            "{} is not a valid input. try key=value instead",
+            input
+        )),
+    }
+}
+
+/// An input declared under a workflow's `on.workflow_dispatch.inputs`
+#[derive(Debug, Default, Deserialize)]
+struct DispatchInput {
+    #[serde(default)]
+    required: bool,
+    default: Option<serde_yaml::Value>,
+}
+
+/// Inputs a workflow definition declares for workflow_dispatch events, by name
+fn dispatch_inputs(yaml: &str) -> Result<BTreeMap<String, DispatchInput>, serde_yaml::Error> {
+    let definition: serde_yaml::Value = serde_yaml::from_str(yaml)?;
+    match definition
+        .get("on")
+        .and_then(|on| on.get("workflow_dispatch"))
+        .and_then(|dispatch| dispatch.get("inputs"))
+    {
+        Some(inputs) if !inputs.is_null() => {
+            let inputs: BTreeMap<String, Option<DispatchInput>> =
+                serde_yaml::from_value(inputs.clone())?;
+            Ok(inputs
+                .into_iter()
+                .map(|(name, input)| (name, input.unwrap_or_default()))
+                .collect())
+        }
+        _ => Ok(BTreeMap::new()),
+    }
+}
+
+/// Rejects inputs a workflow doesn't declare, returning the names of required inputs
+/// without a default that weren't provided
+fn check_inputs(
+    declared: &BTreeMap<String, DispatchInput>,
+    inputs: &[(String, String)],
+) -> Result<Vec<String>, StringErr> {
+    let unknown = inputs
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .filter(|name| !declared.contains_key(*name))
+        .collect::<Vec<_>>();
+    if !unknown.is_empty() {
+        return Err(StringErr(format!(
+            "The workflow does not declare input(s) {}. Declared inputs are {}",
+            unknown.join(", "),
+            if declared.is_empty() {
+                "none".to_string()
+            } else {
+                declared.keys().cloned().collect::<Vec<_>>().join(", ")
+            }
+        )));
+    }
+    Ok(declared
+        .iter()
+        .filter(|(name, input)| {
+            input.required
+                && input.default.is_none()
+                && !inputs.iter().any(|(provided, _)| provided == *name)
+        })
+        .map(|(name, _)| name.clone())
+        .collect())
+}
+
+/// Parses a yyyy-mm month into the start of that month and the start of the next
+fn parse_month(month: &str) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let start = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| format!("{} is not a valid month. try yyyy-mm instead", month))?;
+    let end = match start.month() {
+        12 => NaiveDate::from_ymd(start.year() + 1, 1, 1),
+        month => NaiveDate::from_ymd(start.year(), month + 1, 1),
+    };
+    Ok((
+        Utc.from_utc_datetime(&start.and_hms(0, 0, 0)),
+        Utc.from_utc_datetime(&end.and_hms(0, 0, 0)),
+    ))
 }
 
-fn filtered_workflows(
+/// Workflows matching `workflow` by name or file name, as `matches` does, or every workflow
+pub(crate) fn filtered_workflows(
     workflow: Option<String>,
-    workflows: impl Stream<Item = Workflow>,
-) -> impl Stream<Item = Workflow> {
-    workflows.filter(move |flow| {
-        let matched = workflow.as_ref().map_or(true, |name| {
-            flow.name.to_lowercase().contains(&name.to_lowercase())
-        });
+    exact: bool,
+    workflows: impl Stream<Item = Result<Workflow, ActionsError>>,
+) -> impl Stream<Item = Result<Workflow, ActionsError>> {
+    workflows.try_filter(move |flow| {
+        let matched = workflow
+            .as_ref()
+            .is_none_or(|workflow| matches(flow, workflow, exact));
         async move { matched }
     })
 }
 
-pub async fn workflows(args: Workflows) -> Result<(), Box<dyn Error>> {
-    match args {
-        Workflows::Usage {
-            repository,
+/// Whether a workflow's name or file name is, or when not exact contains ignoring case, `workflow`
+fn matches(
+    flow: &Workflow,
+    workflow: &str,
+    exact: bool,
+) -> bool {
+    if exact {
+        return flow.name == workflow || flow.filename() == workflow;
+    }
+    let workflow = workflow.to_lowercase();
+    flow.name.to_lowercase().contains(&workflow)
+        || flow.filename().to_lowercase().contains(&workflow)
+}
+
+/// Resolves a single workflow by its file name or, failing that, its name
+pub(crate) async fn resolve_workflow(
+    requests: &Requests,
+    repository: &str,
+    workflow: &str,
+) -> Result<Workflow, Box<dyn Error>> {
+    let workflows = requests
+        .clone()
+        .workflows(repository.into())
+        .try_collect::<Vec<_>>()
+        .await?;
+    if let Some(flow) = workflows.iter().find(|flow| flow.filename() == workflow) {
+        return Ok(flow.clone());
+    }
+    let mut matched = filtered_workflows(
+        Some(workflow.into()),
+        false,
+        stream::iter(workflows.into_iter().map(Ok)),
+    )
+    .try_collect::<Vec<_>>()
+    .await?;
+    match matched.len() {
+        0 => Err(StringErr(format!("No workflow matching {} was found", workflow)).into()),
+        1 => Ok(matched.remove(0)),
+        _ => Err(StringErr(format!(
+            "{} matches multiple workflows: {}. try a workflow file name instead",
             workflow,
-        } => {
-            let mut writer = TabWriter::new(stdout());
-
-            let client = Client::new();
-            let token = env::var("GITHUB_TOKEN")
-                .map_err(|_| StringErr("Please provide a GITHUB_TOKEN env variable".into()))?;
-            let requests = Requests { client, token };
-
-            writeln!(writer, "Workflow\tLinux\tMacOs\tWindows")?;
-            let mut workflows =
-                filtered_workflows(workflow, requests.clone().workflows(repository.clone()))
-                    .boxed();
-            let sum = std::rc::Rc::new(std::cell::RefCell::new(Duration::default()));
-            while let Some(workflow) = Pin::new(&mut workflows).next().await {
-                let usage = requests
+            matched
+                .iter()
+                .map(|flow| format!("{} ({})", flow.name, flow.filename()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+        .into()),
+    }
+}
+
+/// Enables or disables a workflow, then reports the state GitHub now has for it
+async fn toggle_workflow(
+    config: Config,
+    workflow: String,
+    enabled: bool,
+) -> Result<(), Box<dyn Error>> {
+    let repository = config.repository()?;
+    let requests = config.requests()?;
+    let workflow = resolve_workflow(&requests, &repository, &workflow).await?;
+    requests
+        .set_workflow_enabled(&repository, workflow.id, enabled)
+        .await?;
+    if config.dry_run {
+        return Ok(());
+    }
+    let workflow = requests.workflow(&repository, workflow.id).await?;
+    println!("{} is {}", workflow.name.bold(), workflow.state);
+    Ok(())
+}
+
+pub async fn workflows(
+    args: Workflows,
+    config: Config,
+) -> Result<(), Box<dyn Error>> {
+    match args {
+        Workflows::Usage { workflow, format } => {
+            let repository = config.repository()?;
+            let mut table = Table::new(format).quiet(config.quiet);
+
+            let requests = config.requests()?;
+
+            if config.output == Output::Text {
+                table.header(&[&"Workflow", &"Linux", &"MacOs", &"Windows"])?;
+            }
+            let mut workflows = filtered_workflows(
+                workflow,
+                false,
+                requests.clone().workflows(repository.clone()),
+            )
+            .boxed();
+            let mut listed = Vec::new();
+            let mut summary = UsageSummary::default();
+            while let Some(workflow) = Pin::new(&mut workflows).try_next().await? {
+                // one workflow's usage failing shouldn't abort the rest of the report
+                let usage = match requests
                     .workflow_usage(repository.clone(), workflow.id)
-                    .await?;
-                let ubuntu = usage.ubuntu();
-                let macos = usage.macos();
-                let windows = usage.windows();
-                *sum.borrow_mut() += ubuntu + macos + windows;
-                writeln!(
-                    writer,
-                    "{}\t{}\t{}\t{}",
-                    workflow.name.bold(),
-                    format_duration(ubuntu),
-                    format_duration(macos),
-                    format_duration(windows),
-                )?;
+                    .await
+                {
+                    Ok(usage) => usage,
+                    Err(err) => {
+                        summary.skipped += 1;
+                        eprintln!(
+                            "{}",
+                            format!("Usage of {} could not be fetched: {}", workflow.name, err)
+                                .red()
+                        );
+                        if config.output == Output::Text {
+                            table.row(&[&workflow.name.bold(), &"-", &"-", &"-"])?;
+                        }
+                        continue;
+                    }
+                };
+                let usage = Usage {
+                    workflow: workflow.name,
+                    ubuntu_ms: usage.ubuntu(),
+                    macos_ms: usage.macos(),
+                    windows_ms: usage.windows(),
+                };
+                summary.add(&usage);
+                match config.output {
+                    Output::Json => listed.push(usage),
+                    Output::JsonLines => print_json_line(&usage)?,
+                    Output::Text => table.row(&[
+                        &usage.workflow.bold(),
+                        &format_duration(usage.ubuntu_ms),
+                        &format_duration(usage.macos_ms),
+                        &format_duration(usage.windows_ms),
+                    ])?,
+                }
+            }
+            match config.output {
+                Output::Json => {
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&UsageReport {
+                            workflows: listed,
+                            summary,
+                        })?
+                    );
+                    return Ok(());
+                }
+                Output::JsonLines => return Ok(()),
+                Output::Text => (),
             }
-            writer.flush()?;
-            println!(
-                "\nTotal minutes spent {}",
-                (sum.borrow().as_secs() / 60).to_string().bold()
+            table.flush()?;
+            if table.format() == Format::Tab && !config.quiet {
+                println!(
+                    "\nTotal minutes spent {}",
+                    (summary.total_ms.as_secs() / 60).to_string().bold()
+                );
+                if summary.skipped > 0 {
+                    println!(
+                        "{} workflow(s) skipped because their usage could not be fetched",
+                        summary.skipped
+                    );
+                }
+            }
+        }
+        Workflows::Billing { month } => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let workflows = requests.clone().workflows(repository.clone()).err_into();
+            let usages = match month {
+                // per workflow usage only covers the current billing cycle, so a month's
+                // usage is summed from the usage of each of its runs
+                Some((since, until)) => {
+                    let filter = RunFilter {
+                        since,
+                        until: Some(until),
+                        status: None,
+                        branch: None,
+                        event: None,
+                        actor: None,
+                    };
+                    let (runs, repo) = (requests.clone(), repository.clone());
+                    workflows
+                        .map_ok(move |workflow| {
+                            runs.clone()
+                                .runs(repo.clone(), workflow.id.to_string(), filter.clone())
+                                .err_into()
+                        })
+                        .try_flatten()
+                        .map_ok(|run| requests.run_usage(&repository, run.id))
+                        .try_buffered(config.concurrency)
+                        .try_collect::<Vec<_>>()
+                        .await?
+                }
+                None => {
+                    workflows
+                        .map_ok(|workflow| requests.workflow_usage(repository.clone(), workflow.id))
+                        .try_buffered(config.concurrency)
+                        .try_collect::<Vec<_>>()
+                        .await?
+                }
+            };
+            let total = |os: fn(&WorkflowUsage) -> Duration| {
+                usages.iter().map(os).sum::<Duration>().as_secs() / 60
+            };
+            let (ubuntu, macos, windows) = (
+                total(WorkflowUsage::ubuntu),
+                total(WorkflowUsage::macos),
+                total(WorkflowUsage::windows),
             );
+            let mut table = Table::new(Format::Tab).quiet(config.quiet);
+            table.header(&[&"OS", &"Minutes"])?;
+            table.row(&[&"Linux", &ubuntu])?;
+            table.row(&[&"MacOs", &macos])?;
+            table.row(&[&"Windows", &windows])?;
+            table.row(&[
+                &"Total".bold(),
+                &(ubuntu + macos + windows).to_string().bold(),
+            ])?;
+            table.flush()?;
+            let owner = repository.split('/').next().unwrap_or_default();
+            if let Ok(billing) = requests.actions_billing(owner).await {
+                println!(
+                    "\n{} of {} included minutes remaining for {}",
+                    billing
+                        .included_minutes
+                        .saturating_sub(billing.total_minutes_used)
+                        .to_string()
+                        .bold(),
+                    billing.included_minutes,
+                    owner
+                );
+            }
         }
         Workflows::List {
-            repository,
             workflow,
+            exact,
+            include_disabled,
+            org,
+            repos_file,
+            format,
         } => {
-            let mut writer = TabWriter::new(stdout());
-
-            let client = Client::new();
-            let token = env::var("GITHUB_TOKEN")
-                .map_err(|_| StringErr("Please provide a GITHUB_TOKEN env variable".into()))?;
-            let requests = Requests { client, token };
-
-            writeln!(writer, "Workflow\tPath")?;
-            let mut workflows =
-                filtered_workflows(workflow, requests.clone().workflows(repository.clone()))
-                    .boxed();
-            while let Some(workflow) = Pin::new(&mut workflows).next().await {
-                writeln!(
-                    writer,
-                    "{}\t{}",
-                    workflow.name.bold(),
-                    workflow.path.dimmed()
-                )?;
+            let mut table = Table::with_pager(format, config.pager()).quiet(config.quiet);
+
+            let requests = config.requests()?;
+
+            let with_repository = org.is_some() || repos_file.is_some();
+            let repos = match (org, repos_file) {
+                (Some(org), _) => {
+                    let progress = config.progress(&format!("Discovering {} repos", org));
+                    let repos = requests.clone().repos(org, usize::MAX, &progress).await;
+                    progress.finish_and_clear();
+                    let (repos, incomplete) = repos?;
+                    if incomplete {
+                        eprintln!(
+                            "{}",
+                            "Code search returned incomplete results, so some repos may be missing"
+                                .yellow()
+                        );
+                    }
+                    Some(repos.into_iter().map(|repo| repo.full_name).collect())
+                }
+                (None, Some(repos_file)) => Some(read_repos_file(&repos_file)?),
+                (None, None) => None,
+            };
+            let workflows = match repos {
+                Some(repos) => {
+                    // each repo's workflows are fetched at once but listed in repo order
+                    stream::iter(repos)
+                        .map(move |repo: String| {
+                            filtered_workflows(
+                                workflow.clone(),
+                                exact,
+                                requests.clone().workflows(repo.clone()),
+                            )
+                            .map_ok(move |workflow| Listed {
+                                repository: Some(repo.clone()),
+                                workflow,
+                            })
+                            .try_collect::<Vec<_>>()
+                        })
+                        .buffered(config.concurrency)
+                        .map_ok(|listed| stream::iter(listed.into_iter().map(Ok)))
+                        .try_flatten()
+                        .boxed()
+                }
+                None => filtered_workflows(
+                    workflow,
+                    exact,
+                    requests.clone().workflows(config.repository()?),
+                )
+                .map_ok(|workflow| Listed {
+                    repository: None,
+                    workflow,
+                })
+                .boxed(),
+            };
+            let mut workflows = workflows
+                .try_filter(move |listed| {
+                    future::ready(include_disabled || listed.workflow.active())
+                })
+                .take(config.limit());
+            if let Some(fields) = &config.fields {
+                return print_fields(&config, fields, format, workflows).await;
             }
-            writer.flush()?;
+            match config.output {
+                Output::Json => return print_json(&workflows.try_collect::<Vec<_>>().await?),
+                Output::JsonLines => return print_json_lines(workflows).await,
+                Output::Text => (),
+            }
+            if with_repository {
+                table.header(&[&"Repository", &"Workflow", &"Path"])?;
+            } else {
+                table.header(&[&"Workflow", &"Path"])?;
+            }
+            while let Some(listed) = Pin::new(&mut workflows).try_next().await? {
+                workflow_row(&mut table, &listed)?;
+            }
+            table.flush()?;
+        }
+        Workflows::Dispatch {
+            workflow,
+            git_ref,
+            inputs,
+            no_validate,
+        } => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let workflow = resolve_workflow(&requests, &repository, &workflow).await?;
+            if !no_validate {
+                // a definition which can't be fetched or parsed is left for the api to judge
+                match requests
+                    .file_contents(&repository, &workflow.path, Some(&git_ref))
+                    .await
+                    .map_err(Box::<dyn Error>::from)
+                    .and_then(|yaml| Ok(dispatch_inputs(&yaml)?))
+                {
+                    Ok(declared) => {
+                        let missing = check_inputs(&declared, &inputs)?;
+                        if !missing.is_empty() {
+                            eprintln!(
+                                "{}",
+                                format!(
+                                    "Required input(s) {} were not provided",
+                                    missing.join(", ")
+                                )
+                                .yellow()
+                            );
+                        }
+                    }
+                    Err(err) => eprintln!(
+                        "{}",
+                        format!("Inputs could not be validated: {}", err).yellow()
+                    ),
+                }
+            }
+            requests
+                .dispatch(
+                    &repository,
+                    workflow.id,
+                    &Dispatch {
+                        git_ref: git_ref.clone(),
+                        inputs: inputs.into_iter().collect(),
+                    },
+                )
+                .await?;
+            config.changed(format!(
+                "Dispatched {} on {}",
+                workflow.name.bold(),
+                git_ref
+            ));
+        }
+        Workflows::Enable { workflow } => toggle_workflow(config, workflow, true).await?,
+        Workflows::Disable { workflow } => toggle_workflow(config, workflow, false).await?,
+        Workflows::Show { workflow, raw } => {
+            let repository = config.repository()?;
+            let requests = config.requests()?;
+            let workflow = resolve_workflow(&requests, &repository, &workflow).await?;
+            let yaml = requests
+                .file_contents(&repository, &workflow.path, None)
+                .await?;
+            if !raw {
+                println!("{}", workflow.name.bold());
+                println!("id    {}", workflow.id);
+                println!("state {}", workflow.state);
+                println!("path  {}\n", workflow.path.dimmed());
+            }
+            print!("{}", yaml);
         }
     }
 
@@ -128,28 +673,73 @@ mod tests {
     use futures::stream;
     use futures_await_test::async_test;
 
+    #[test]
+    fn parse_month_spans_the_whole_month() {
+        assert_eq!(
+            parse_month("2020-02"),
+            Ok((
+                Utc.ymd(2020, 2, 1).and_hms(0, 0, 0),
+                Utc.ymd(2020, 3, 1).and_hms(0, 0, 0)
+            ))
+        );
+        assert_eq!(
+            parse_month("2020-12").map(|(_, end)| end),
+            Ok(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0))
+        );
+        assert!(parse_month("2020-13").is_err());
+    }
+
+    #[async_test]
+    async fn filtered_workflows_filters_workflows_by_name_or_file_name() {
+        let workflows = || {
+            stream::iter(vec![
+                Ok(Workflow {
+                    id: 1,
+                    name: "Tests".into(),
+                    state: "active".into(),
+                    path: ".github/workflows/ci.yml".into(),
+                }),
+                Ok(Workflow {
+                    id: 2,
+                    name: "Release".into(),
+                    state: "active".into(),
+                    path: ".github/workflows/release.yml".into(),
+                }),
+            ])
+        };
+        let ids = |workflow: &str| {
+            filtered_workflows(Some(workflow.into()), false, workflows())
+                .map_ok(|flow| flow.id)
+                .try_collect::<Vec<_>>()
+        };
+        assert_eq!(ids("ci.yml").await.unwrap(), vec![1]);
+        assert_eq!(ids("release").await.unwrap(), vec![2]);
+    }
+
     #[async_test]
     async fn filtered_workflows_filters_workflows_by_name() {
         assert_eq!(
             filtered_workflows(
                 Some("CI".into()),
+                false,
                 stream::iter(vec![
-                    Workflow {
+                    Ok(Workflow {
                         id: 1,
                         name: "ci test".into(),
                         state: "completed".into(),
                         path: ".github/workflows".into()
-                    },
-                    Workflow {
+                    }),
+                    Ok(Workflow {
                         id: 2,
                         name: "test".into(),
                         state: "completed".into(),
                         path: ".github/workflows".into()
-                    }
+                    })
                 ])
             )
-            .collect::<Vec<_>>()
-            .await,
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap(),
             vec![Workflow {
                 id: 1,
                 name: "ci test".into(),
@@ -158,4 +748,150 @@ mod tests {
             }]
         );
     }
+
+    #[test]
+    fn matches_workflow_names_and_file_names() {
+        let flow = Workflow {
+            id: 1,
+            name: "Tests".into(),
+            state: "active".into(),
+            path: ".github/workflows/ci.yml".into(),
+        };
+        assert!(matches(&flow, "test", false));
+        assert!(matches(&flow, "CI.YML", false));
+        assert!(matches(&flow, "ci", false));
+        assert!(!matches(&flow, "ci", true));
+        assert!(!matches(&flow, "test", true));
+        assert!(matches(&flow, "ci.yml", true));
+        assert!(matches(&flow, "Tests", true));
+    }
+
+    #[test]
+    fn listed_workflows_only_include_repository_of_org_listings() {
+        let listed = |repository: Option<&str>| Listed {
+            repository: repository.map(String::from),
+            workflow: Workflow {
+                id: 1,
+                name: "CI".into(),
+                state: "active".into(),
+                path: ".github/workflows/ci.yml".into(),
+            },
+        };
+        assert_eq!(
+            serde_json::to_string(&listed(None)).unwrap(),
+            r#"{"id":1,"name":"CI","state":"active","path":".github/workflows/ci.yml"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&listed(Some("acme/api"))).unwrap(),
+            r#"{"repository":"acme/api","id":1,"name":"CI","state":"active","path":".github/workflows/ci.yml"}"#
+        );
+    }
+
+    #[test]
+    fn usage_serializes_durations_as_milliseconds() {
+        let usage = Usage {
+            workflow: "CI".into(),
+            ubuntu_ms: Duration::from_millis(180_000),
+            macos_ms: Duration::from_millis(1_500),
+            windows_ms: Duration::default(),
+        };
+        let mut summary = UsageSummary::default();
+        summary.add(&usage);
+        assert_eq!(
+            serde_json::to_string(&UsageReport {
+                workflows: vec![usage],
+                summary,
+            })
+            .unwrap(),
+            r#"{"workflows":[{"workflow":"CI","ubuntu_ms":180000,"macos_ms":1500,"windows_ms":0}],"summary":{"ubuntu_ms":180000,"macos_ms":1500,"windows_ms":0,"total_ms":181500,"skipped":0}}"#
+        );
+    }
+
+    #[test]
+    fn dispatch_inputs_are_read_from_the_workflow_dispatch_trigger() {
+        let declared = dispatch_inputs(
+            "name: deploy\n\
+             on:\n  \
+               push:\n  \
+               workflow_dispatch:\n    \
+                 inputs:\n      \
+                   environment:\n        \
+                     required: true\n      \
+                   version:\n        \
+                     required: true\n        \
+                     default: latest\n      \
+                   dry-run:\n",
+        )
+        .unwrap();
+        assert_eq!(
+            declared.keys().collect::<Vec<_>>(),
+            vec!["dry-run", "environment", "version"]
+        );
+        assert!(dispatch_inputs("on: [push, workflow_dispatch]")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn check_inputs_rejects_unknown_and_returns_missing_required_inputs() {
+        let declared = dispatch_inputs(
+            "on:\n  \
+               workflow_dispatch:\n    \
+                 inputs:\n      \
+                   environment:\n        \
+                     required: true\n      \
+                   version:\n        \
+                     required: true\n        \
+                     default: latest\n",
+        )
+        .unwrap();
+        assert_eq!(
+            check_inputs(&declared, &[]).unwrap(),
+            vec!["environment".to_string()]
+        );
+        assert!(
+            check_inputs(&declared, &[("environment".into(), "prod".into())])
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            check_inputs(&declared, &[("enviroment".into(), "prod".into())])
+                .unwrap_err()
+                .0,
+            "The workflow does not declare input(s) enviroment. Declared inputs are environment, version"
+        );
+    }
+
+    #[test]
+    fn workflow_rows_tag_inactive_workflows() {
+        let listed = |repository: Option<&str>, name: &str, state: &str| Listed {
+            repository: repository.map(String::from),
+            workflow: Workflow {
+                id: 1,
+                name: name.into(),
+                state: state.into(),
+                path: format!(".github/workflows/{}.yml", name),
+            },
+        };
+        let mut out = Vec::new();
+        let mut table = Table::with_writer(Format::Csv, &mut out);
+        workflow_row(&mut table, &listed(None, "ci", "active")).unwrap();
+        workflow_row(
+            &mut table,
+            &listed(Some("acme/api"), "nightly", "disabled_manually"),
+        )
+        .unwrap();
+        drop(table);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "ci,.github/workflows/ci.yml\n\
+             acme/api,nightly [disabled],.github/workflows/nightly.yml\n"
+        );
+    }
+
+    #[test]
+    fn parse_input_splits_on_first_equals() {
+        assert_eq!(parse_input("query=a=b"), Ok(("query".into(), "a=b".into())));
+        assert!(parse_input("query").is_err());
+    }
 }